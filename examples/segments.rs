@@ -1,7 +1,6 @@
 extern crate tdms;
 
 use std::path::Path;
-use tdms::data_type::TdmsDataType;
 use tdms::TDMSFile;
 
 fn main() {
@@ -20,23 +19,16 @@ fn main() {
         let channels = file.channels(&group);
 
         for (_, channel) in channels {
-            // the returned full channel is an iterator over raw data
-            let full_channel = match channel.data_type {
-                // the returned full channel is an iterator over raw data
-                TdmsDataType::DoubleFloat(_) => file.channel_data_double_float(channel),
-                _ => {
-                    panic!("{}", "channel for data type unimplemented")
-                }
-            };
-
-            let full_channel_iterator = match full_channel {
-                Ok(i) => i,
+            // any_channel_data reads the channel's raw data as whichever native type matches its
+            // data type
+            let values = match file.any_channel_data(channel) {
+                Ok(v) => v,
                 Err(e) => {
                     panic!("{:?}", e)
                 }
             };
 
-            println!("{:?}", full_channel_iterator.count());
+            println!("{:?}", values.len());
         }
     }
 }
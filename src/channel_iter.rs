@@ -1,26 +1,39 @@
-use crate::data_type::{TdmsDataType, TdmsTimestamp};
+use crate::data_type::{TdmsDataType, TdmsNative};
 use crate::segment::{Channel, ChannelPositions};
-use crate::TdmsError::{ChannelDoesNotExist, EndOfSegments, GroupDoesNotExist};
+use crate::TdmsError::{
+    ChannelDoesNotExist, CorruptChannelData, EndOfSegments, GroupDoesNotExist, TruncatedChannelData,
+};
 use crate::{Endianness, General, Segment, TdmsError};
 use log::error;
 use std::cell::RefCell;
 use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom};
 use std::marker::PhantomData;
-use std::string::FromUtf8Error;
+
+/// `ChannelDataPosition` records where a `ChannelDataIter` will read its next value. Iterators are
+/// tied to the segments of the `TDMSFile` that created them, so after `TDMSFile::refresh` a new
+/// iterator can be created and `resume`d from the position of the old one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChannelDataPosition {
+    pub segment_start_pos: u64,
+    pub stream_pos: u64,
+    pub string_offset_index: usize,
+    pub string_previous_offset: u32,
+}
 
 #[derive(Debug)]
 pub struct ChannelDataIter<'a, T, R: Read + Seek> {
     channel: RefCell<&'a Channel>,
-    current_pos: RefCell<ChannelPositions>,
     segments: Vec<&'a Segment>,
     reader: BufReader<R>,
     current_segment_index: RefCell<usize>,
+    current_chunk_index: RefCell<usize>,
+    // position of the next value to read, and of the reader if known
+    next_pos: u64,
+    reader_pos: Option<u64>,
     // string channel type specific fields
-    current_segment_offsets: RefCell<Vec<u32>>,
     string_offsets: RefCell<Vec<u32>>,
     string_offset_index: RefCell<usize>,
     string_previous_offset: RefCell<u32>,
-    offset_index: RefCell<usize>,
     _mask: PhantomData<T>,
 }
 
@@ -43,109 +56,231 @@ impl<'a, T, R: Read + Seek> ChannelDataIter<'a, T, R> {
                 Some(c) => c,
             };
 
-        let first_pos = match channel.chunk_positions.get(0) {
-            None => ChannelPositions(0, 0),
-            Some(p) => p.clone(),
-        };
-
         let channel = RefCell::new(channel);
 
         let mut iter = ChannelDataIter {
-            current_pos: RefCell::new(first_pos),
             channel,
             segments,
             reader,
             current_segment_index: RefCell::new(0),
-            current_segment_offsets: RefCell::new(vec![]),
-            offset_index: RefCell::new(0),
+            current_chunk_index: RefCell::new(0),
+            next_pos: 0,
+            reader_pos: None,
             _mask: Default::default(),
             string_offset_index: RefCell::new(0),
             string_offsets: RefCell::new(vec![]),
             string_previous_offset: RefCell::new(0),
         };
 
-        iter.set_string_offsets()?;
+        iter.set_chunk(0)?;
 
-        // set the reader to the first segment's start position so that the rest of the reader works
-        // correctly
-        match iter.segments.get(0) {
-            None => {}
-            Some(s) => {
-                iter.reader.seek(SeekFrom::Start(s.start_pos))?;
-            }
-        }
+        Ok(iter)
+    }
+
+    /// `position` returns the position of the next value this iterator will read. Pass it to
+    /// `resume` on an iterator for the same channel to continue reading from there.
+    pub fn position(&self) -> ChannelDataPosition {
+        let index = *self.current_segment_index.borrow();
 
-        return Ok(iter);
+        ChannelDataPosition {
+            segment_start_pos: self.segments[index].start_pos,
+            stream_pos: self.next_pos,
+            string_offset_index: *self.string_offset_index.borrow(),
+            string_previous_offset: *self.string_previous_offset.borrow(),
+        }
     }
 
-    fn set_string_offsets(&mut self) -> Result<(), TdmsError> {
-        // first zero out the values
-        self.string_offsets.swap(&RefCell::new(vec![]));
-        self.string_offset_index.swap(&RefCell::new(0));
-        match self.channel.get_mut().string_offset_pos {
-            None => {}
-            Some(offset_pos) => {
-                // switch the reader to the start of the offsets
-                self.reader.seek(SeekFrom::Start(offset_pos.0))?;
-
-                loop {
-                    if self.reader.stream_position()? >= offset_pos.1 {
-                        break;
-                    }
+    /// `resume` moves the iterator to a position previously returned by `position`, typically from
+    /// an iterator created before the file was refreshed. The segment the position points into
+    /// must still be part of the file, it may have grown since.
+    pub fn resume(&mut self, position: ChannelDataPosition) -> Result<(), TdmsError> {
+        let index = match self
+            .segments
+            .iter()
+            .position(|s| s.start_pos == position.segment_start_pos)
+        {
+            None => {
+                return Err(General(format!(
+                    "no segment starting at {} holds data for this channel",
+                    position.segment_start_pos
+                )))
+            }
+            Some(i) => i,
+        };
 
-                    let mut buf: [u8; 4] = [0; 4];
-                    self.reader.read_exact(&mut buf)?;
+        self.set_segment(index)?;
 
-                    let current_segment = match self.segments.get(0) {
-                        None => return Err(EndOfSegments()),
-                        Some(s) => s
-                    };
+        // string offsets are read a chunk at a time, the position's index counts from the start of
+        // the chunk it points into
+        let chunk = self
+            .channel
+            .borrow()
+            .chunk_positions
+            .iter()
+            .position(|p| p.0 <= position.stream_pos && position.stream_pos <= p.1)
+            .unwrap_or(0);
+        self.set_chunk(chunk)?;
 
-                    let offset = match current_segment.endianess() {
-                        Endianness::Little => {u32::from_le_bytes(buf)}
-                        Endianness::Big => {u32::from_be_bytes(buf)}
-                    };
+        self.next_pos = position.stream_pos;
+        self.string_offset_index
+            .swap(&RefCell::new(position.string_offset_index));
+        self.string_previous_offset
+            .swap(&RefCell::new(position.string_previous_offset));
 
-                    self.string_offsets.get_mut().push(offset);
-                }
-            }
+        Ok(())
+    }
+
+    /// `sample_index` returns the index of the next value this iterator will read, counting from
+    /// the channel's first value in the file
+    pub fn sample_index(&self) -> u64 {
+        let index = *self.current_segment_index.borrow();
+        let chunk = *self.current_chunk_index.borrow();
+        let channel = *self.channel.borrow();
+
+        let mut sample: u64 = self.segments[..index]
+            .iter()
+            .filter_map(|s| s.get_channel(&channel.group_path, &channel.path))
+            .map(|c| {
+                (0..c.chunk_positions.len())
+                    .map(|i| c.values_in_chunk(i))
+                    .sum::<u64>()
+            })
+            .sum();
+        sample += (0..chunk).map(|i| channel.values_in_chunk(i)).sum::<u64>();
+
+        let ChannelPositions(start, _) = match channel.chunk_positions.get(chunk) {
+            None => return sample,
+            Some(positions) => *positions,
         };
 
-        Ok(())
+        let in_chunk = match channel.stride() {
+            0 => *self.string_offset_index.borrow() as u64,
+            stride => self.next_pos.saturating_sub(start).div_ceil(stride),
+        };
+
+        sample + in_chunk.min(channel.values_in_chunk(chunk))
     }
 
-    fn current_positions(&mut self, stream_pos: u64) -> Result<(), TdmsError> {
-        if stream_pos < self.current_pos.borrow().1 {
-            return Ok(());
-        }
+    /// `seek_to_sample` moves the iterator to the channel's value at index `n`, counting from the
+    /// channel's first value in the file, working out its position from the chunk sizes rather than
+    /// reading the values before it. Seeking to the channel's length leaves the iterator at the end,
+    /// seeking further returns an error and does the same
+    pub fn seek_to_sample(&mut self, n: u64) -> Result<(), TdmsError> {
+        let channel = *self.channel.borrow();
+        let mut remaining = n;
+
+        for index in 0..self.segments.len() {
+            let segment_channel =
+                match self.segments[index].get_channel(&channel.group_path, &channel.path) {
+                    None => continue,
+                    Some(c) => c,
+                };
+
+            for chunk in 0..segment_channel.chunk_positions.len() {
+                let values = segment_channel.values_in_chunk(chunk);
+                if remaining >= values {
+                    remaining -= values;
+                    continue;
+                }
 
-        for positions in self.channel.borrow().chunk_positions.iter() {
-            if stream_pos >= positions.1 {
-                continue;
+                self.set_segment(index)?;
+                self.set_chunk(chunk)?;
+
+                let ChannelPositions(start, _) = segment_channel.chunk_positions[chunk];
+                match segment_channel.stride() {
+                    0 => {
+                        let previous = match remaining {
+                            0 => 0,
+                            _ => self.string_offsets.borrow()[remaining as usize - 1],
+                        };
+
+                        self.string_offset_index
+                            .swap(&RefCell::new(remaining as usize));
+                        self.string_previous_offset.swap(&RefCell::new(previous));
+                        self.next_pos = start + previous as u64;
+                    }
+                    stride => self.next_pos = start + remaining * stride,
+                }
+
+                return Ok(());
             }
+        }
 
-            self.current_pos.swap(&RefCell::new(positions.clone()));
-            return Ok(());
+        // past the last value, leave the iterator at the end of its last segment
+        let last = self.segments.len() - 1;
+        self.set_segment(last)?;
+        let chunks = self.channel.borrow().chunk_positions.len();
+        self.set_chunk(chunks)?;
+
+        match remaining {
+            0 => Ok(()),
+            _ => Err(General(format!(
+                "sample {} is past the end of the channel's {} values",
+                n,
+                n - remaining
+            ))),
         }
+    }
 
-        let index = self.current_segment_index.take();
+    /// set_chunk moves the iterator to the chunk at `chunk` in the current segment. Every chunk of a
+    /// string channel starts with the offsets of its own strings, which are read here
+    fn set_chunk(&mut self, chunk: usize) -> Result<(), TdmsError> {
+        self.current_chunk_index.swap(&RefCell::new(chunk));
+        self.string_offsets.swap(&RefCell::new(vec![]));
+        self.string_offset_index.swap(&RefCell::new(0));
+        self.string_previous_offset.swap(&RefCell::new(0));
 
-        let mut current_segment = match self.segments.get(index) {
-            None => return Err(EndOfSegments()),
-            Some(s) => s,
+        let channel = *self.channel.borrow();
+        let (offsets, positions) = match (
+            channel.string_offset_pos,
+            channel.chunk_positions.get(chunk),
+        ) {
+            (Some(offsets), Some(positions)) => (offsets, *positions),
+            _ => return Ok(()),
         };
 
-        if stream_pos != current_segment.start_pos {
-            self.reader.seek(SeekFrom::Start(current_segment.end_pos))?;
-            current_segment = match self.segments.get(index + 1) {
+        let current_segment: &Segment =
+            match self.segments.get(*self.current_segment_index.borrow()) {
                 None => return Err(EndOfSegments()),
-                Some(s) => {
-                    self.current_segment_index.swap(&RefCell::new(index + 1));
-                    s
-                }
+                Some(s) => s,
             };
+
+        // `string_offset_pos` holds the first chunk's offsets, in every chunk they come right
+        // before the strings themselves
+        let end = positions.0;
+        let start = end - (offsets.1 - offsets.0);
+
+        // switch the reader to the start of the offsets
+        self.reader.seek(SeekFrom::Start(start))?;
+        self.reader_pos = None;
+
+        let mut pos = start;
+        while pos + 4 <= end {
+            let mut buf: [u8; 4] = [0; 4];
+            self.reader.read_exact(&mut buf)?;
+            pos += 4;
+
+            let offset = match current_segment.endianess() {
+                Endianness::Little => u32::from_le_bytes(buf),
+                Endianness::Big => u32::from_be_bytes(buf),
+            };
+
+            self.string_offsets.get_mut().push(offset);
         }
 
+        self.reader_pos = Some(pos);
+
+        Ok(())
+    }
+
+    /// set_segment switches the iterator to the start of the segment at `index` in its segment list,
+    /// along with that segment's version of the channel
+    fn set_segment(&mut self, index: usize) -> Result<(), TdmsError> {
+        let current_segment = match self.segments.get(index) {
+            None => return Err(EndOfSegments()),
+            Some(s) => s,
+        };
+
         // we can error out here because if this is a new segment, but that segment doesn't
         // have the channels we want, we need to error out
         let channels = match current_segment
@@ -167,660 +302,302 @@ impl<'a, T, R: Read + Seek> ChannelDataIter<'a, T, R> {
         };
 
         self.channel.swap(&RefCell::new(channel));
-        self.set_string_offsets()?;
-
-        for positions in self.channel.borrow().chunk_positions.iter() {
-            if stream_pos >= positions.1 {
-                continue;
-            }
-
-            self.current_pos.swap(&RefCell::new(positions.clone()));
-            return Ok(());
-        }
-
-        return Err(EndOfSegments());
+        self.current_segment_index.swap(&RefCell::new(index));
+        self.next_pos = 0;
+        self.set_chunk(0)
     }
 
     /// advance_reader_to_next moves the internal BufReader<R> to the next valid data value depending
     /// on data type, index, current pos. etc - this function also handles iterating to the next
     /// segment if necessary
-    fn advance_reader_to_next(&mut self) -> Result<&Segment, TdmsError> {
-        let mut stream_pos = self.reader.stream_position()?;
-        self.current_positions(stream_pos)?;
-        let start_pos = self.current_pos.borrow().0;
-        let end_pos = self.current_pos.borrow().1;
-
-        let index = self.current_segment_index.clone().take();
-
-        let current_segment = match self.segments.get(index) {
-            None => return Err(EndOfSegments()),
-            Some(s) => s,
-        };
-
-        // if we're not past data start, move us there first
-        if stream_pos < current_segment.start_pos + current_segment.lead_in.raw_data_offset
-            || stream_pos < start_pos
-        {
-            self.reader.seek(SeekFrom::Start(start_pos))?;
-            stream_pos = start_pos;
-        }
-
-        // if we're past the channel's end pos for the segment, move to the end of segment and
-        // recursively call this function - setting the new channel's raw index and calculating
-        // start and end pos if needed
-        if stream_pos >= current_segment.end_pos {
-            self.reader.seek(SeekFrom::Start(current_segment.end_pos))?;
-
-            let current_segment = match self.segments.get(index + 1) {
-                None => return Err(EndOfSegments()),
-                Some(s) => {
-                    self.current_segment_index.swap(&RefCell::new(index + 1));
-                    s
+    fn advance_reader_to_next(&mut self) -> Result<&'a Segment, TdmsError> {
+        loop {
+            let chunk = *self.current_chunk_index.borrow();
+            let positions = self.channel.borrow().chunk_positions.get(chunk).copied();
+
+            match positions {
+                // out of chunks in this segment, move to the next one
+                None => {
+                    let index = *self.current_segment_index.borrow();
+                    self.set_segment(index + 1)?;
                 }
-            };
-
-            // we can error out here because if this is a new segment, but that segment doesn't
-            // have the channels we want, we need to error out
-            let channels = match current_segment
-                .groups
-                .get(&self.channel.borrow().group_path)
-            {
-                None => return Err(GroupDoesNotExist()),
-                Some(g) => g,
-            };
-
-            let channel_map = match channels {
-                None => return Err(ChannelDoesNotExist()),
-                Some(c) => c,
-            };
-
-            let channel = match channel_map.get(&self.channel.borrow().path) {
-                None => return Err(ChannelDoesNotExist()),
-                Some(channel) => channel,
-            };
-
-            self.channel.swap(&RefCell::new(channel));
-            self.set_string_offsets()?;
-
-            return self.advance_reader_to_next();
-        }
-
-        // iterate by interleaved offset if interleaved data
-        if current_segment.has_interleaved_data() {
-            self.reader.seek(SeekFrom::Current(
-                self.channel.borrow().interleaved_offset as i64,
-            ))?;
-
-            return self.advance_reader_to_next();
-        }
-
-        if stream_pos >= start_pos && stream_pos < end_pos {
-            return Ok(current_segment);
-        }
-
-        return self.advance_reader_to_next();
-    }
-}
-
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, f64, R> {
-    type Item = f64;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
-        let current_segment = self.advance_reader_to_next();
-        let endianess = match current_segment {
-            Err(e) => {
-                match e {
-                    EndOfSegments() => (),
-                    _ => error!("error reading next value in channel: {:?}", e),
-                }
-
-                return None;
-            }
-            Ok(s) => s.endianess(),
-        };
+                Some(ChannelPositions(start, end)) => {
+                    if self.next_pos < start {
+                        self.next_pos = start;
+                    }
 
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 8] = [0; 8];
+                    if self.next_pos < end {
+                        break;
+                    }
 
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
+                    self.set_chunk(chunk + 1)?;
                 }
-
-                return None;
             }
         }
 
-        return match endianess {
-            Endianness::Little => Some(f64::from_le_bytes(buf)),
-            Endianness::Big => Some(f64::from_be_bytes(buf)),
-        };
-    }
-}
-
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, i8, R> {
-    type Item = i8;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
-        let current_segment = self.advance_reader_to_next();
-        let endianess = match current_segment {
-            Err(e) => {
-                match e {
-                    EndOfSegments() => (),
-                    _ => error!("error reading next value in channel: {:?}", e),
-                }
-
-                return None;
+        // only seek when we have to, as seeking discards the BufReader's buffer
+        match self.reader_pos {
+            Some(pos) if pos == self.next_pos => (),
+            Some(pos) => {
+                self.reader
+                    .seek_relative(self.next_pos as i64 - pos as i64)?;
             }
-            Ok(s) => s.endianess(),
-        };
-
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 1] = [0; 1];
-
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
-
-                return None;
+            None => {
+                self.reader.seek(SeekFrom::Start(self.next_pos))?;
             }
         }
+        self.reader_pos = Some(self.next_pos);
 
-        return match endianess {
-            Endianness::Little => Some(i8::from_le_bytes(buf)),
-            Endianness::Big => Some(i8::from_be_bytes(buf)),
-        };
+        let index = *self.current_segment_index.borrow();
+        Ok(self.segments[index])
     }
-}
-
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, i16, R> {
-    type Item = i16;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
-        let current_segment = self.advance_reader_to_next();
-        let endianess = match current_segment {
-            Err(e) => {
-                match e {
-                    EndOfSegments() => (),
-                    _ => error!("error reading next value in channel: {:?}", e),
-                }
-
-                return None;
-            }
-            Ok(s) => s.endianess(),
-        };
-
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 2] = [0; 2];
-
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
 
-                return None;
+    /// next_string_size returns the size in bytes of the next string in the current segment, working
+    /// it out from the string's end offset and the end offset of the string before it
+    fn next_string_size(&mut self) -> Result<usize, TdmsError> {
+        let index = *self.string_offset_index.borrow();
+        let offset = match self.string_offsets.borrow().get(index) {
+            None => {
+                return Err(CorruptChannelData(String::from(
+                    "string channel has more data than string offsets",
+                )))
             }
-        }
-
-        return match endianess {
-            Endianness::Little => Some(i16::from_le_bytes(buf)),
-            Endianness::Big => Some(i16::from_be_bytes(buf)),
+            Some(offset) => *offset,
         };
-    }
-}
-
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, i32, R> {
-    type Item = i32;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
-        let current_segment = self.advance_reader_to_next();
-        let endianess = match current_segment {
-            Err(e) => {
-                match e {
-                    EndOfSegments() => (),
-                    _ => error!("error reading next value in channel: {:?}", e),
-                }
-
-                return None;
+        let size = match offset.checked_sub(*self.string_previous_offset.borrow()) {
+            None => {
+                return Err(CorruptChannelData(format!(
+                    "string offset {} is before the end of the previous string",
+                    offset
+                )))
             }
-            Ok(s) => s.endianess(),
+            Some(size) => size,
         };
 
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 4] = [0; 4];
+        self.string_previous_offset.swap(&RefCell::new(offset));
+        self.string_offset_index.swap(&RefCell::new(index + 1));
 
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
+        Ok(size as usize)
+    }
 
-                return None;
-            }
+    /// read_value reads the value at the reader's position into `buf` and works out where the next
+    /// value of the channel starts - for interleaved data that is after the other channels' values
+    fn read_value(&mut self, buf: &mut [u8]) -> Result<(), std::io::Error> {
+        if let Err(e) = self.reader.read_exact(buf) {
+            self.reader_pos = None;
+            return Err(e);
         }
 
-        return match endianess {
-            Endianness::Little => Some(i32::from_le_bytes(buf)),
-            Endianness::Big => Some(i32::from_be_bytes(buf)),
-        };
+        let pos = self.next_pos + buf.len() as u64;
+        self.reader_pos = Some(pos);
+        self.next_pos = pos + self.channel.borrow().interleaved_offset;
+
+        Ok(())
     }
 }
 
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, i64, R> {
-    type Item = i64;
-
-    fn next(&mut self) -> Option<Self::Item> {
+impl<'a, T: TdmsNative, R: Read + Seek> ChannelDataIter<'a, T, R> {
+    /// `try_next` reads the next value of the channel, returning `None` once the channel's data in
+    /// every segment has been read. Unlike `next` it returns errors instead of stopping on them - a
+    /// `TruncatedChannelData` error means the file ends before the channel's data does, and a
+    /// `CorruptChannelData` error that the channel's string offsets don't make sense. The iterator
+    /// doesn't move past a value it failed to read, calling `try_next` again retries it
+    pub fn try_next(&mut self) -> Option<Result<T, TdmsError>> {
         // advance to next value - this function handles interleaved iteration and moving to the
         // next segment
-        let current_segment = self.advance_reader_to_next();
-        let endianess = match current_segment {
-            Err(e) => {
-                match e {
-                    EndOfSegments() => (),
-                    _ => error!("error reading next value in channel: {:?}", e),
-                }
-
-                return None;
-            }
+        let endianess = match self.advance_reader_to_next() {
+            Err(EndOfSegments()) => return None,
+            Err(e) => return Some(Err(e)),
             Ok(s) => s.endianess(),
         };
 
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 8] = [0; 8];
-
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
-
-                return None;
-            }
+        // to check the required byte size of this channel's data type, look at data_types.rs and
+        // the TdmsDataType enum. Strings vary in size, their offsets tell us how large each is
+        let data_type = self.channel.borrow().raw_data_type();
+        let string_cursor = (
+            *self.string_offset_index.borrow(),
+            *self.string_previous_offset.borrow(),
+        );
+        let size = match data_type {
+            TdmsDataType::String => match self.next_string_size() {
+                Err(e) => return Some(Err(e)),
+                Ok(size) => size,
+            },
+            data_type => TdmsDataType::get_size(data_type),
+        };
+
+        // fixed size values fit on the stack, only strings need allocating
+        let mut buf: [u8; 16] = [0; 16];
+        let mut vec: Vec<u8>;
+        let bytes = if size <= buf.len() {
+            &mut buf[..size]
+        } else {
+            vec = vec![0; size];
+            &mut vec[..]
+        };
+
+        if let Err(e) = self.read_value(bytes) {
+            // the string's offset has already been used up, put it back so a retry reads it again
+            let (index, previous_offset) = string_cursor;
+            self.string_offset_index.swap(&RefCell::new(index));
+            self.string_previous_offset
+                .swap(&RefCell::new(previous_offset));
+
+            return Some(Err(match e.kind() {
+                ErrorKind::UnexpectedEof => TruncatedChannelData(),
+                _ => e.into(),
+            }));
         }
 
-        return match endianess {
-            Endianness::Little => Some(i64::from_le_bytes(buf)),
-            Endianness::Big => Some(i64::from_be_bytes(buf)),
-        };
+        Some(T::read_from_data_type(bytes, data_type, endianess))
     }
-}
-
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, u8, R> {
-    type Item = u8;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
-        let current_segment = self.advance_reader_to_next();
-        let endianess = match current_segment {
-            Err(e) => {
-                match e {
-                    EndOfSegments() => (),
-                    _ => error!("error reading next value in channel: {:?}", e),
-                }
-
-                return None;
-            }
-            Ok(s) => s.endianess(),
-        };
 
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 1] = [0; 1];
-
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
-
-                return None;
-            }
+    /// `try_iter` borrows the iterator as one yielding a `Result` for each value, so that errors
+    /// can be told apart from the end of the channel's data. Iteration stops after the first error
+    pub fn try_iter(&mut self) -> TryChannelDataIter<'_, 'a, T, R> {
+        TryChannelDataIter {
+            iter: self,
+            failed: false,
         }
-
-        return match endianess {
-            Endianness::Little => Some(u8::from_le_bytes(buf)),
-            Endianness::Big => Some(u8::from_be_bytes(buf)),
-        };
     }
-}
 
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, u16, R> {
-    type Item = u16;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
-        let current_segment = self.advance_reader_to_next();
-        let endianess = match current_segment {
-            Err(e) => {
-                match e {
-                    EndOfSegments() => (),
-                    _ => error!("error reading next value in channel: {:?}", e),
-                }
+    /// `read_into` fills `values` with the channel's next values, returning how many were read -
+    /// fewer than `values.len()` only once the channel's data runs out. Contiguous values are read a
+    /// chunk at a time rather than one by one, interleaved and string data falls back to `try_next`.
+    /// On error the values before the failed read have already been written and the iterator is
+    /// left at the first value that wasn't returned
+    pub fn read_into(&mut self, values: &mut [T]) -> Result<usize, TdmsError> {
+        let mut values = values.iter_mut();
 
-                return None;
+        self.read_values(values.len(), |value| {
+            if let Some(slot) = values.next() {
+                *slot = value;
             }
-            Ok(s) => s.endianess(),
-        };
+        })
+    }
 
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 2] = [0; 2];
+    /// `read_range` reads `len` values starting at the channel's value at index `start`, fewer if
+    /// the channel ends first, seeking to `start` with `seek_to_sample`
+    pub fn read_range(&mut self, start: u64, len: usize) -> Result<Vec<T>, TdmsError> {
+        self.seek_to_sample(start)?;
 
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
+        let mut values = Vec::with_capacity(len);
+        self.read_values(len, |value| values.push(value))?;
 
-                return None;
-            }
-        }
+        Ok(values)
+    }
 
-        return match endianess {
-            Endianness::Little => Some(u16::from_le_bytes(buf)),
-            Endianness::Big => Some(u16::from_be_bytes(buf)),
-        };
+    /// `read_all` reads all of the channel's remaining values, see `read_into`
+    pub fn read_all(&mut self) -> Result<Vec<T>, TdmsError> {
+        let mut values = vec![];
+        self.read_values(usize::MAX, |value| values.push(value))?;
+
+        Ok(values)
     }
-}
 
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, u32, R> {
-    type Item = u32;
+    /// read_values reads up to `max` values, passing each to `f` in order, and returns the number read
+    fn read_values<F: FnMut(T)>(&mut self, max: usize, mut f: F) -> Result<usize, TdmsError> {
+        let mut read = 0;
+        let mut bytes: Vec<u8> = vec![];
 
-    fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
-        let current_segment = self.advance_reader_to_next();
-        let endianess = match current_segment {
-            Err(e) => {
-                match e {
-                    EndOfSegments() => (),
-                    _ => error!("error reading next value in channel: {:?}", e),
-                }
+        while read < max {
+            let endianess = match self.advance_reader_to_next() {
+                Err(EndOfSegments()) => break,
+                Err(e) => return Err(e),
+                Ok(s) => s.endianess(),
+            };
 
-                return None;
-            }
-            Ok(s) => s.endianess(),
-        };
+            let channel = *self.channel.borrow();
+            let data_type = channel.raw_data_type();
+            let size = TdmsDataType::get_size(data_type) as u64;
+            let chunk = *self.current_chunk_index.borrow();
+            let end = channel.chunk_positions[chunk].1;
 
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 4] = [0; 4];
+            let count = match data_type {
+                TdmsDataType::String => 0,
+                _ if size == 0 || channel.interleaved_offset != 0 => 0,
+                _ => ((end - self.next_pos) / size).min((max - read) as u64) as usize,
+            };
 
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
+            if count == 0 {
+                match self.try_next() {
+                    None => break,
+                    Some(value) => f(value?),
                 }
 
-                return None;
+                read += 1;
+                continue;
             }
-        }
 
-        return match endianess {
-            Endianness::Little => Some(u32::from_le_bytes(buf)),
-            Endianness::Big => Some(u32::from_be_bytes(buf)),
-        };
-    }
-}
-
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, u64, R> {
-    type Item = u64;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
-        let current_segment = self.advance_reader_to_next();
-        let endianess = match current_segment {
-            Err(e) => {
-                match e {
-                    EndOfSegments() => (),
-                    _ => error!("error reading next value in channel: {:?}", e),
-                }
+            bytes.resize(count * size as usize, 0);
+            if let Err(e) = self.reader.read_exact(&mut bytes) {
+                self.reader_pos = None;
 
-                return None;
+                return Err(match e.kind() {
+                    ErrorKind::UnexpectedEof => TruncatedChannelData(),
+                    _ => e.into(),
+                });
             }
-            Ok(s) => s.endianess(),
-        };
 
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 8] = [0; 8];
+            self.next_pos += bytes.len() as u64;
+            self.reader_pos = Some(self.next_pos);
 
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
-
-                return None;
+            for value in bytes.chunks_exact(size as usize) {
+                f(T::read_from_data_type(value, data_type, endianess)?);
             }
+
+            read += count;
         }
 
-        return match endianess {
-            Endianness::Little => Some(u64::from_le_bytes(buf)),
-            Endianness::Big => Some(u64::from_be_bytes(buf)),
-        };
+        Ok(read)
     }
 }
 
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, f32, R> {
-    type Item = f32;
+impl<'a, T: TdmsNative, R: Read + Seek> Iterator for ChannelDataIter<'a, T, R> {
+    type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
-        let current_segment = self.advance_reader_to_next();
-        let endianess = match current_segment {
+        match self.try_next()? {
+            Ok(v) => Some(v),
             Err(e) => {
                 match e {
-                    EndOfSegments() => (),
+                    TruncatedChannelData() => (),
                     _ => error!("error reading next value in channel: {:?}", e),
                 }
 
-                return None;
-            }
-            Ok(s) => s.endianess(),
-        };
-
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 4] = [0; 4];
-
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
-
-                return None;
+                None
             }
         }
-
-        return match endianess {
-            Endianness::Little => Some(f32::from_le_bytes(buf)),
-            Endianness::Big => Some(f32::from_be_bytes(buf)),
-        };
     }
-}
-
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, bool, R> {
-    type Item = bool;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
 
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 1] = [0; 1];
+    /// skips to the `n`th value by seeking rather than reading the values before it
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let sample = self.sample_index().checked_add(n as u64)?;
+        self.seek_to_sample(sample).ok()?;
 
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
-
-                return None;
-            }
-        }
-
-        return Some(buf[0] != 0);
+        self.next()
     }
 }
 
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, String, R> {
-    type Item = String;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
-        let current_segment = self.advance_reader_to_next();
-
-        // to check the required byte size of this channel's data type we must used the string offset
-        // vector to determine how large to make this.
-        let index = self.string_offset_index.borrow().clone();
-        let size = match self.string_offsets.borrow().get(index) {
-            None => {
-                return None;
-            }
-            Some(o) => {
-                let result = o.clone() - self.string_previous_offset.borrow().clone();
-                self.string_previous_offset.swap(&RefCell::new(o.clone()));
-                result
-            }
-        };
-
-
-        self.string_offset_index.swap(&RefCell::new(index +1));
-
-        let mut vec = vec![0; size as usize];
-
-        match self.reader.read_exact(&mut vec) {
-            Ok(_) => {}
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
-
-                println!("{:?}", e);
-
-                return None;
-            }
-        }
-
-        match String::from_utf8(vec) {
-            Ok(s) => {
-                return Some(s)
-            }
-            Err(e) => {
-                error!("unable to cast TDMS string to UTF8 String {:?}", e);
-                return None;
-            }
-        }
-    }
+/// `TryChannelDataIter` iterates through a channel's values like `ChannelDataIter`, but yields
+/// `Result`s rather than logging errors and stopping. Create one with `ChannelDataIter::try_iter`.
+pub struct TryChannelDataIter<'i, 'a, T, R: Read + Seek> {
+    iter: &'i mut ChannelDataIter<'a, T, R>,
+    failed: bool,
 }
 
-impl<'a, R: Read + Seek> Iterator for ChannelDataIter<'a, TdmsTimestamp, R> {
-    type Item = TdmsTimestamp;
+impl<'i, 'a, T: TdmsNative, R: Read + Seek> Iterator for TryChannelDataIter<'i, 'a, T, R> {
+    type Item = Result<T, TdmsError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // advance to next value - this function handles interleaved iteration and moving to the
-        // next segment
-        let current_segment = self.advance_reader_to_next();
-        let endianess = match current_segment {
-            Err(e) => {
-                match e {
-                    EndOfSegments() => (),
-                    _ => error!("error reading next value in channel: {:?}", e),
-                }
-
-                return None;
-            }
-            Ok(s) => s.endianess(),
-        };
-
-        // to check the required byte size of this channel's data type, look
-        // at data_types.rs and the TdmsDataType enum
-        let mut buf: [u8; 8] = [0; 8];
-
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
-
-                return None;
-            }
+        if self.failed {
+            return None;
         }
 
-        let seconds_since_epoch = match endianess {
-            Endianness::Little => i64::from_le_bytes(buf),
-            Endianness::Big => i64::from_be_bytes(buf),
-        };
-
-        let mut buf: [u8; 8] = [0; 8];
-
-        match self.reader.read_exact(&mut buf) {
-            Ok(_) => (),
-            Err(e) => {
-                match e.kind() {
-                    ErrorKind::UnexpectedEof => {}
-                    _ => error!("error reading value from file: {:?}", e),
-                }
-
-                return None;
-            }
-        }
-
-        let fractions_of_second = match endianess {
-            Endianness::Little => u64::from_le_bytes(buf),
-            Endianness::Big => u64::from_be_bytes(buf),
-        };
+        let result = self.iter.try_next()?;
+        self.failed = result.is_err();
 
-        return Some(TdmsTimestamp(seconds_since_epoch, fractions_of_second));
+        Some(result)
     }
 }
@@ -0,0 +1,80 @@
+use crate::data_type::{Extended, TdmsComplex, TdmsTimestamp};
+use crate::fixed_point::FixedPoint;
+
+#[derive(Debug, Clone, PartialEq)]
+/// `ChannelValues` holds a channel's raw data as a `Vec` of the native Rust type matching the
+/// channel's data type. It is returned by `TDMSFile::any_channel_data` for tools that only find out
+/// a channel's data type at runtime. Float types with units are held as their plain equivalents.
+pub enum ChannelValues {
+    Void,
+    I8(Vec<i8>),
+    I16(Vec<i16>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+    U64(Vec<u64>),
+    SingleFloat(Vec<f32>),
+    DoubleFloat(Vec<f64>),
+    ExtendedFloat(Vec<Extended>),
+    String(Vec<String>),
+    Boolean(Vec<bool>),
+    TimeStamp(Vec<TdmsTimestamp>),
+    FixedPoint(Vec<FixedPoint>),
+    ComplexSingleFloat(Vec<TdmsComplex<f32>>),
+    ComplexDoubleFloat(Vec<TdmsComplex<f64>>),
+}
+
+impl ChannelValues {
+    /// `len` returns the number of values held
+    pub fn len(&self) -> usize {
+        match self {
+            ChannelValues::Void => 0,
+            ChannelValues::I8(v) => v.len(),
+            ChannelValues::I16(v) => v.len(),
+            ChannelValues::I32(v) => v.len(),
+            ChannelValues::I64(v) => v.len(),
+            ChannelValues::U8(v) => v.len(),
+            ChannelValues::U16(v) => v.len(),
+            ChannelValues::U32(v) => v.len(),
+            ChannelValues::U64(v) => v.len(),
+            ChannelValues::SingleFloat(v) => v.len(),
+            ChannelValues::DoubleFloat(v) => v.len(),
+            ChannelValues::ExtendedFloat(v) => v.len(),
+            ChannelValues::String(v) => v.len(),
+            ChannelValues::Boolean(v) => v.len(),
+            ChannelValues::TimeStamp(v) => v.len(),
+            ChannelValues::FixedPoint(v) => v.len(),
+            ChannelValues::ComplexSingleFloat(v) => v.len(),
+            ChannelValues::ComplexDoubleFloat(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// `ChannelValue` holds a single value of a channel as the native Rust type matching the channel's
+/// data type, the way `ChannelValues` holds all of them. Rows of a `GroupRowIter` are made of these.
+pub enum ChannelValue {
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    SingleFloat(f32),
+    DoubleFloat(f64),
+    ExtendedFloat(Extended),
+    String(String),
+    Boolean(bool),
+    TimeStamp(TdmsTimestamp),
+    FixedPoint(FixedPoint),
+    ComplexSingleFloat(TdmsComplex<f32>),
+    ComplexDoubleFloat(TdmsComplex<f64>),
+}
@@ -1,5 +1,26 @@
-use crate::{Big, Endianness, General, Little, TdmsError, UnknownDataType};
-use std::io::{Read, Seek};
+use crate::TdmsError::DataTypeMismatch;
+use crate::{Big, Endianness, General, Little, StringConversionError, TdmsError, UnknownDataType};
+pub use extended::Extended;
+use std::fmt;
+use std::io;
+use std::io::{Read, Write};
+
+/// `read_bytes` reads `length` bytes, typically a length prefixed string. The length comes from the
+/// file itself, so the buffer grows as bytes are read instead of being allocated up front - a
+/// corrupt length fails once the reader runs out of bytes rather than allocating gigabytes
+pub(crate) fn read_bytes<R: Read>(r: &mut R, length: usize) -> Result<Vec<u8>, TdmsError> {
+    let mut bytes: Vec<u8> = vec![];
+    r.take(length as u64).read_to_end(&mut bytes)?;
+
+    if bytes.len() < length {
+        return Err(General(format!(
+            "string of {} bytes runs past the end of the metadata",
+            length
+        )));
+    }
+
+    Ok(bytes)
+}
 
 /// Represents the potential TDMS data types. Contained value is size in bytes if applicable
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -52,14 +73,43 @@ impl TryFrom<i32> for TdmsDataType {
             x if x == 0x21 => Ok(TdmsDataType::Boolean(1)),
             x if x == 0x44 => Ok(TdmsDataType::TimeStamp(16)),
             x if x == 0x4f => Ok(TdmsDataType::FixedPoint(10)),
-            x if x == 0x08000c => Ok(TdmsDataType::ComplexSingleFloat(4)),
-            x if x == 0x10000d => Ok(TdmsDataType::ComplexDoubleFloat(8)),
+            0x08000c => Ok(TdmsDataType::ComplexSingleFloat(8)),
+            0x10000d => Ok(TdmsDataType::ComplexDoubleFloat(16)),
             x if x == -1 => Ok(TdmsDataType::DAQmxRawData), // 0xFFFFFFFF equivalent
             _ => Err(UnknownDataType()),
         }
     }
 }
 
+impl From<TdmsDataType> for i32 {
+    fn from(data_type: TdmsDataType) -> Self {
+        match data_type {
+            TdmsDataType::Void => 0,
+            TdmsDataType::I8(_) => 1,
+            TdmsDataType::I16(_) => 2,
+            TdmsDataType::I32(_) => 3,
+            TdmsDataType::I64(_) => 4,
+            TdmsDataType::U8(_) => 5,
+            TdmsDataType::U16(_) => 6,
+            TdmsDataType::U32(_) => 7,
+            TdmsDataType::U64(_) => 8,
+            TdmsDataType::SingleFloat(_) => 9,
+            TdmsDataType::DoubleFloat(_) => 10,
+            TdmsDataType::ExtendedFloat(_) => 11,
+            TdmsDataType::SingleFloatWithUnit(_) => 0x19,
+            TdmsDataType::DoubleFloatWithUnit(_) => 0x1a,
+            TdmsDataType::ExtendedFloatWithUnit(_) => 0x1b,
+            TdmsDataType::String => 0x20,
+            TdmsDataType::Boolean(_) => 0x21,
+            TdmsDataType::TimeStamp(_) => 0x44,
+            TdmsDataType::FixedPoint(_) => 0x4f,
+            TdmsDataType::ComplexSingleFloat(_) => 0x08000c,
+            TdmsDataType::ComplexDoubleFloat(_) => 0x10000d,
+            TdmsDataType::DAQmxRawData => -1,
+        }
+    }
+}
+
 impl TdmsDataType {
     pub fn get_size(data_type: TdmsDataType) -> usize {
         return match data_type {
@@ -102,7 +152,7 @@ pub struct TDMSValue {
 impl TDMSValue {
     /// from_reader accepts an open reader and a data type and attempts to read, generating a
     /// value struct containing the actual value
-    pub fn from_reader<R: Read + Seek>(
+    pub fn from_reader<R: Read>(
         endianness: Endianness,
         data_type: TdmsDataType,
         r: &mut R,
@@ -272,8 +322,7 @@ impl TDMSValue {
                     }
                 };
 
-                let mut value = vec![0; length];
-                r.read_exact(&mut value)?;
+                let value = read_bytes(r, length)?;
 
                 Ok(TDMSValue {
                     data_type,
@@ -301,8 +350,8 @@ impl TDMSValue {
                     value: Some(buf.to_vec()),
                 })
             }
-            // there is little information on how to handle FixedPoint types, for
-            // now we'll store them as a 64 bit integer and hope that will be enough
+            // fixed point values are stored as an integer, interpreting it requires the encoding
+            // kept in separate properties - see fixed_point.rs
             TdmsDataType::FixedPoint(_) => {
                 let mut buf: [u8; 10] = [0; 10];
                 r.read_exact(&mut buf)?;
@@ -345,7 +394,467 @@ impl TDMSValue {
             }
         };
     }
+
+    /// `to_native` decodes the value as the native Rust type `T`, returning a `DataTypeMismatch`
+    /// error if the value's data type can't be read as `T`. Extended precision values, for example,
+    /// can be read losslessly as `Extended` or rounded as `f64`
+    pub fn to_native<T: TdmsNative>(&self) -> Result<T, TdmsError> {
+        if !T::reads_data_type(self.data_type) {
+            return Err(DataTypeMismatch(T::DATA_TYPE, self.data_type));
+        }
+
+        match &self.value {
+            None => Err(General(String::from("value is empty"))),
+            Some(bytes) => T::read_from_data_type(bytes, self.data_type, self.endianness),
+        }
+    }
+
+    /// `as_f64` returns an integer or floating point value as `f64`, rounding 64 bit integers and
+    /// extended precision values if needed
+    pub fn as_f64(&self) -> Result<f64, TdmsError> {
+        match self.data_type {
+            TdmsDataType::I8(_) => Ok(self.to_native::<i8>()? as f64),
+            TdmsDataType::I16(_) => Ok(self.to_native::<i16>()? as f64),
+            TdmsDataType::I32(_) => Ok(self.to_native::<i32>()? as f64),
+            TdmsDataType::I64(_) => Ok(self.to_native::<i64>()? as f64),
+            TdmsDataType::U8(_) => Ok(self.to_native::<u8>()? as f64),
+            TdmsDataType::U16(_) => Ok(self.to_native::<u16>()? as f64),
+            TdmsDataType::U32(_) => Ok(self.to_native::<u32>()? as f64),
+            TdmsDataType::U64(_) => Ok(self.to_native::<u64>()? as f64),
+            TdmsDataType::SingleFloat(_) | TdmsDataType::SingleFloatWithUnit(_) => {
+                Ok(self.to_native::<f32>()? as f64)
+            }
+            _ => self.to_native::<f64>(),
+        }
+    }
+
+    /// `as_i64` returns an integer value as `i64`, failing for unsigned values too large to fit
+    pub fn as_i64(&self) -> Result<i64, TdmsError> {
+        match self.data_type {
+            TdmsDataType::I8(_) => Ok(self.to_native::<i8>()? as i64),
+            TdmsDataType::I16(_) => Ok(self.to_native::<i16>()? as i64),
+            TdmsDataType::I32(_) => Ok(self.to_native::<i32>()? as i64),
+            TdmsDataType::U8(_) => Ok(self.to_native::<u8>()? as i64),
+            TdmsDataType::U16(_) => Ok(self.to_native::<u16>()? as i64),
+            TdmsDataType::U32(_) => Ok(self.to_native::<u32>()? as i64),
+            TdmsDataType::U64(_) => Ok(i64::try_from(self.to_native::<u64>()?)?),
+            _ => self.to_native::<i64>(),
+        }
+    }
+
+    /// `as_u64` returns a non-negative integer value as `u64`
+    pub fn as_u64(&self) -> Result<u64, TdmsError> {
+        match self.data_type {
+            TdmsDataType::U64(_) => self.to_native::<u64>(),
+            _ => Ok(u64::try_from(self.as_i64()?)?),
+        }
+    }
+
+    /// `as_str` returns a string value without copying it
+    pub fn as_str(&self) -> Result<&str, TdmsError> {
+        if self.data_type != TdmsDataType::String {
+            return Err(DataTypeMismatch(TdmsDataType::String, self.data_type));
+        }
+
+        match std::str::from_utf8(self.value.as_deref().unwrap_or_default()) {
+            Ok(s) => Ok(s),
+            Err(_) => Err(StringConversionError(String::from(
+                "unable to convert string value",
+            ))),
+        }
+    }
+
+    /// `as_bool` returns a boolean value
+    pub fn as_bool(&self) -> Result<bool, TdmsError> {
+        self.to_native()
+    }
+
+    /// `as_timestamp` returns a timestamp value
+    pub fn as_timestamp(&self) -> Result<TdmsTimestamp, TdmsError> {
+        self.to_native()
+    }
+}
+
+impl fmt::Display for TDMSValue {
+    /// formats the decoded value - numbers and strings as Rust would format them, timestamps as
+    /// ISO-8601 and complex numbers as `re+imi`. Values that can't be decoded are shown as hex
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let formatted = match self.data_type {
+            TdmsDataType::Void => Ok(String::new()),
+            TdmsDataType::I8(_)
+            | TdmsDataType::I16(_)
+            | TdmsDataType::I32(_)
+            | TdmsDataType::I64(_)
+            | TdmsDataType::U8(_)
+            | TdmsDataType::U16(_)
+            | TdmsDataType::U32(_) => self.as_i64().map(|v| v.to_string()),
+            TdmsDataType::U64(_) => self.as_u64().map(|v| v.to_string()),
+            TdmsDataType::SingleFloat(_) | TdmsDataType::SingleFloatWithUnit(_) => {
+                self.to_native::<f32>().map(|v| v.to_string())
+            }
+            TdmsDataType::DoubleFloat(_)
+            | TdmsDataType::DoubleFloatWithUnit(_)
+            | TdmsDataType::ExtendedFloat(_)
+            | TdmsDataType::ExtendedFloatWithUnit(_) => self.as_f64().map(|v| v.to_string()),
+            TdmsDataType::String => self.as_str().map(String::from),
+            TdmsDataType::Boolean(_) => self.as_bool().map(|v| v.to_string()),
+            TdmsDataType::TimeStamp(_) => self.as_timestamp().map(|v| v.to_string()),
+            TdmsDataType::ComplexSingleFloat(_) => self
+                .to_native::<TdmsComplex<f32>>()
+                .map(|v| format!("{}{:+}i", v.re, v.im)),
+            TdmsDataType::ComplexDoubleFloat(_) => self
+                .to_native::<TdmsComplex<f64>>()
+                .map(|v| format!("{}{:+}i", v.re, v.im)),
+            _ => Ok(hex::encode(self.value.as_deref().unwrap_or_default())),
+        };
+
+        match formatted {
+            Ok(s) => f.write_str(&s),
+            Err(_) => f.write_str(&hex::encode(self.value.as_deref().unwrap_or_default())),
+        }
+    }
+}
+
+macro_rules! impl_try_from_tdms_value {
+    ( $( $t:ty ),* ) => {
+        $(
+        impl TryFrom<&TDMSValue> for $t {
+            type Error = TdmsError;
+
+            fn try_from(value: &TDMSValue) -> Result<Self, Self::Error> {
+                value.to_native()
+            }
+        }
+        )*
+    };
 }
 
-#[derive(Clone, Debug, Copy)]
+impl_try_from_tdms_value!(
+    i8,
+    i16,
+    i32,
+    i64,
+    u8,
+    u16,
+    u32,
+    u64,
+    f32,
+    f64,
+    bool,
+    String,
+    Extended,
+    TdmsTimestamp,
+    TdmsComplex<f32>,
+    TdmsComplex<f64>
+);
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// `TdmsTimestamp` is a point in time as whole seconds since the TDMS epoch, 1904-01-01 00:00:00
+/// UTC, and a positive fraction of a second in units of 2^-64 seconds
 pub struct TdmsTimestamp(pub i64, pub u64);
+
+#[derive(Clone, Debug, Copy, PartialEq, Default)]
+/// `TdmsComplex` is a single complex number, stored as its real part followed by its imaginary part
+pub struct TdmsComplex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+/// `TdmsNative` maps a native Rust type to the TDMS data type it is stored as, and knows how to
+/// encode itself into and decode itself from the raw bytes of a TDMS file.
+pub trait TdmsNative: Sized {
+    /// the TDMS data type values of this type are written as
+    const DATA_TYPE: TdmsDataType;
+
+    /// write_to encodes the value using the requested endianness. Strings are written as their raw
+    /// UTF-8 bytes, any length prefix or offset is the caller's responsibility
+    fn write_to<W: Write>(&self, w: &mut W, endianness: Endianness) -> io::Result<()>;
+
+    /// read_from decodes a single value from `bytes`, which hold exactly one encoded value
+    fn read_from(bytes: &[u8], endianness: Endianness) -> Result<Self, TdmsError>;
+
+    /// reads_data_type returns whether values of a channel with the given data type can be read as
+    /// this type. By default only `DATA_TYPE` can be
+    fn reads_data_type(data_type: TdmsDataType) -> bool {
+        i32::from(data_type) == i32::from(Self::DATA_TYPE)
+    }
+
+    /// read_from_data_type decodes a single value stored as `data_type`, which must be one of the
+    /// data types `reads_data_type` accepts. By default the value is decoded with `read_from`
+    fn read_from_data_type(
+        bytes: &[u8],
+        _data_type: TdmsDataType,
+        endianness: Endianness,
+    ) -> Result<Self, TdmsError> {
+        Self::read_from(bytes, endianness)
+    }
+}
+
+macro_rules! impl_tdms_native {
+    ( $t:ty, $data_type:expr $(, $other:pat )? ) => {
+        impl TdmsNative for $t {
+            const DATA_TYPE: TdmsDataType = $data_type;
+
+            fn write_to<W: Write>(&self, w: &mut W, endianness: Endianness) -> io::Result<()> {
+                match endianness {
+                    Little => w.write_all(&self.to_le_bytes()),
+                    Big => w.write_all(&self.to_be_bytes()),
+                }
+            }
+
+            fn read_from(bytes: &[u8], endianness: Endianness) -> Result<Self, TdmsError> {
+                let buf: [u8; std::mem::size_of::<$t>()] = match bytes.try_into() {
+                    Ok(b) => b,
+                    Err(_) => return Err(General(format!("invalid {} value", stringify!($t)))),
+                };
+
+                Ok(match endianness {
+                    Little => <$t>::from_le_bytes(buf),
+                    Big => <$t>::from_be_bytes(buf),
+                })
+            }
+
+            $(
+            fn reads_data_type(data_type: TdmsDataType) -> bool {
+                data_type == Self::DATA_TYPE || matches!(data_type, $other)
+            }
+            )?
+        }
+    };
+}
+
+impl_tdms_native!(i8, TdmsDataType::I8(1));
+impl_tdms_native!(i16, TdmsDataType::I16(2));
+impl_tdms_native!(i32, TdmsDataType::I32(4));
+impl_tdms_native!(i64, TdmsDataType::I64(8));
+impl_tdms_native!(u8, TdmsDataType::U8(1));
+impl_tdms_native!(u16, TdmsDataType::U16(2));
+impl_tdms_native!(u32, TdmsDataType::U32(4));
+impl_tdms_native!(u64, TdmsDataType::U64(8));
+impl_tdms_native!(
+    f32,
+    TdmsDataType::SingleFloat(4),
+    TdmsDataType::SingleFloatWithUnit(_)
+);
+
+// doubles are also read from extended precision channels, rounding each value to the nearest double
+impl TdmsNative for f64 {
+    const DATA_TYPE: TdmsDataType = TdmsDataType::DoubleFloat(8);
+
+    fn write_to<W: Write>(&self, w: &mut W, endianness: Endianness) -> io::Result<()> {
+        match endianness {
+            Little => w.write_all(&self.to_le_bytes()),
+            Big => w.write_all(&self.to_be_bytes()),
+        }
+    }
+
+    fn read_from(bytes: &[u8], endianness: Endianness) -> Result<Self, TdmsError> {
+        let buf: [u8; 8] = match bytes.try_into() {
+            Ok(b) => b,
+            Err(_) => return Err(General(String::from("invalid f64 value"))),
+        };
+
+        Ok(match endianness {
+            Little => f64::from_le_bytes(buf),
+            Big => f64::from_be_bytes(buf),
+        })
+    }
+
+    fn reads_data_type(data_type: TdmsDataType) -> bool {
+        matches!(
+            data_type,
+            TdmsDataType::DoubleFloat(_)
+                | TdmsDataType::DoubleFloatWithUnit(_)
+                | TdmsDataType::ExtendedFloat(_)
+                | TdmsDataType::ExtendedFloatWithUnit(_)
+        )
+    }
+
+    fn read_from_data_type(
+        bytes: &[u8],
+        data_type: TdmsDataType,
+        endianness: Endianness,
+    ) -> Result<Self, TdmsError> {
+        match data_type {
+            TdmsDataType::ExtendedFloat(_) | TdmsDataType::ExtendedFloatWithUnit(_) => {
+                Ok(Extended::read_from(bytes, endianness)?.to_f64())
+            }
+            _ => Self::read_from(bytes, endianness),
+        }
+    }
+}
+
+impl TdmsNative for Extended {
+    const DATA_TYPE: TdmsDataType = TdmsDataType::ExtendedFloat(10);
+
+    fn write_to<W: Write>(&self, w: &mut W, endianness: Endianness) -> io::Result<()> {
+        match endianness {
+            Little => w.write_all(&self.to_le_bytes()),
+            Big => w.write_all(&self.to_be_bytes()),
+        }
+    }
+
+    // extended precision values are the 80 bit x87 format - a sign bit and 15 bit exponent followed
+    // by a 64 bit fraction with an explicit integer bit, little endian files store the fraction
+    // first
+    fn read_from(bytes: &[u8], endianness: Endianness) -> Result<Self, TdmsError> {
+        let buf: [u8; 10] = match bytes.try_into() {
+            Ok(b) => b,
+            Err(_) => return Err(General(String::from("invalid extended float value"))),
+        };
+
+        Ok(match endianness {
+            Little => Extended::from_le_bytes(buf),
+            Big => Extended::from_be_bytes(buf),
+        })
+    }
+
+    fn reads_data_type(data_type: TdmsDataType) -> bool {
+        matches!(
+            data_type,
+            TdmsDataType::ExtendedFloat(_) | TdmsDataType::ExtendedFloatWithUnit(_)
+        )
+    }
+}
+
+impl TdmsNative for bool {
+    const DATA_TYPE: TdmsDataType = TdmsDataType::Boolean(1);
+
+    fn write_to<W: Write>(&self, w: &mut W, _: Endianness) -> io::Result<()> {
+        w.write_all(&[*self as u8])
+    }
+
+    fn read_from(bytes: &[u8], _: Endianness) -> Result<Self, TdmsError> {
+        match bytes {
+            [b] => Ok(*b != 0),
+            _ => Err(General(String::from("invalid bool value"))),
+        }
+    }
+}
+
+impl TdmsNative for String {
+    const DATA_TYPE: TdmsDataType = TdmsDataType::String;
+
+    fn write_to<W: Write>(&self, w: &mut W, _: Endianness) -> io::Result<()> {
+        w.write_all(self.as_bytes())
+    }
+
+    fn read_from(bytes: &[u8], _: Endianness) -> Result<Self, TdmsError> {
+        match String::from_utf8(bytes.to_vec()) {
+            Ok(s) => Ok(s),
+            Err(_) => Err(StringConversionError(String::from(
+                "unable to convert string value",
+            ))),
+        }
+    }
+}
+
+impl TdmsNative for TdmsTimestamp {
+    const DATA_TYPE: TdmsDataType = TdmsDataType::TimeStamp(16);
+
+    // timestamps are a single 128 bit value - in little endian files the fractions come first
+    fn write_to<W: Write>(&self, w: &mut W, endianness: Endianness) -> io::Result<()> {
+        match endianness {
+            Little => {
+                w.write_all(&self.1.to_le_bytes())?;
+                w.write_all(&self.0.to_le_bytes())
+            }
+            Big => {
+                w.write_all(&self.0.to_be_bytes())?;
+                w.write_all(&self.1.to_be_bytes())
+            }
+        }
+    }
+
+    fn read_from(bytes: &[u8], endianness: Endianness) -> Result<Self, TdmsError> {
+        if bytes.len() != 16 {
+            return Err(General(String::from("invalid timestamp value")));
+        }
+
+        Ok(match endianness {
+            Little => TdmsTimestamp(
+                i64::read_from(&bytes[8..16], endianness)?,
+                u64::read_from(&bytes[0..8], endianness)?,
+            ),
+            Big => TdmsTimestamp(
+                i64::read_from(&bytes[0..8], endianness)?,
+                u64::read_from(&bytes[8..16], endianness)?,
+            ),
+        })
+    }
+}
+
+// complex numbers are a pair of floats, the real part followed by the imaginary part
+macro_rules! impl_tdms_native_complex {
+    ( $complex:ty, $t:ty, $data_type:expr ) => {
+        impl TdmsNative for $complex {
+            const DATA_TYPE: TdmsDataType = $data_type;
+
+            fn write_to<W: Write>(&self, w: &mut W, endianness: Endianness) -> io::Result<()> {
+                self.re.write_to(w, endianness)?;
+                self.im.write_to(w, endianness)
+            }
+
+            fn read_from(bytes: &[u8], endianness: Endianness) -> Result<Self, TdmsError> {
+                let (re, im) = bytes.split_at(bytes.len() / 2);
+
+                Ok(Self {
+                    re: <$t>::read_from(re, endianness)?,
+                    im: <$t>::read_from(im, endianness)?,
+                })
+            }
+        }
+    };
+}
+
+impl_tdms_native_complex!(TdmsComplex<f32>, f32, TdmsDataType::ComplexSingleFloat(8));
+impl_tdms_native_complex!(TdmsComplex<f64>, f64, TdmsDataType::ComplexDoubleFloat(16));
+#[cfg(feature = "num-complex")]
+impl_tdms_native_complex!(
+    num_complex::Complex<f32>,
+    f32,
+    TdmsDataType::ComplexSingleFloat(8)
+);
+#[cfg(feature = "num-complex")]
+impl_tdms_native_complex!(
+    num_complex::Complex<f64>,
+    f64,
+    TdmsDataType::ComplexDoubleFloat(16)
+);
+
+#[cfg(feature = "num-complex")]
+impl<T> From<TdmsComplex<T>> for num_complex::Complex<T> {
+    fn from(value: TdmsComplex<T>) -> Self {
+        num_complex::Complex::new(value.re, value.im)
+    }
+}
+
+#[cfg(feature = "num-complex")]
+impl<T> From<num_complex::Complex<T>> for TdmsComplex<T> {
+    fn from(value: num_complex::Complex<T>) -> Self {
+        TdmsComplex {
+            re: value.re,
+            im: value.im,
+        }
+    }
+}
+
+impl<T: TdmsNative> From<T> for TDMSValue {
+    fn from(value: T) -> Self {
+        let mut buf: Vec<u8> = vec![];
+        // writing into a Vec cannot fail
+        let _ = value.write_to(&mut buf, Little);
+
+        TDMSValue {
+            data_type: T::DATA_TYPE,
+            endianness: Little,
+            value: Some(buf),
+        }
+    }
+}
+
+impl From<&str> for TDMSValue {
+    fn from(value: &str) -> Self {
+        TDMSValue::from(String::from(value))
+    }
+}
@@ -0,0 +1,69 @@
+use crate::channel_iter::ChannelDataIter;
+use crate::TdmsError;
+use std::io::{Read, Seek};
+
+/// `DigitalLineIter` iterates through the states of a single DAQmx digital line, reading the byte
+/// holding the line from each row of the raw buffer and returning its bit.
+pub struct DigitalLineIter<'a, R: Read + Seek> {
+    bytes: ChannelDataIter<'a, u8, R>,
+    bit: u8,
+}
+
+impl<'a, R: Read + Seek> DigitalLineIter<'a, R> {
+    pub(crate) fn new(bytes: ChannelDataIter<'a, u8, R>, bit: u8) -> Self {
+        DigitalLineIter { bytes, bit }
+    }
+
+    /// `bit` returns the position of the line within the bytes it's read from
+    pub fn bit(&self) -> u8 {
+        self.bit
+    }
+
+    /// `try_next` reads the line's next state, returning errors instead of stopping on them like
+    /// `ChannelDataIter::try_next`
+    pub fn try_next(&mut self) -> Option<Result<bool, TdmsError>> {
+        Some(self.bytes.try_next()?.map(|byte| byte >> self.bit & 1 == 1))
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for DigitalLineIter<'a, R> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let byte = self.bytes.next()?;
+
+        Some(byte >> self.bit & 1 == 1)
+    }
+}
+
+/// `DigitalPortIter` packs the states of several digital lines, typically the lines of a single
+/// port, into one value per sample. The first line is the least significant bit. Iteration stops
+/// when any of the lines runs out of samples
+pub struct DigitalPortIter<'a, R: Read + Seek> {
+    lines: Vec<DigitalLineIter<'a, R>>,
+}
+
+impl<'a, R: Read + Seek> DigitalPortIter<'a, R> {
+    pub(crate) fn new(lines: Vec<DigitalLineIter<'a, R>>) -> Self {
+        DigitalPortIter { lines }
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for DigitalPortIter<'a, R> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.lines.is_empty() {
+            return None;
+        }
+
+        let mut value: u64 = 0;
+        for (i, line) in self.lines.iter_mut().enumerate() {
+            if line.next()? {
+                value |= 1 << i;
+            }
+        }
+
+        Some(value)
+    }
+}
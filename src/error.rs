@@ -1,3 +1,4 @@
+use crate::data_type::TdmsDataType;
 use std::io;
 use std::num::TryFromIntError;
 use thiserror::Error;
@@ -27,6 +28,12 @@ pub enum TdmsError {
     #[error("end of segments in file reached")]
     EndOfSegments(),
 
+    #[error("file ends before the end of the channel's data")]
+    TruncatedChannelData(),
+
+    #[error("channel data is corrupt: {0}")]
+    CorruptChannelData(String),
+
     #[error("invalid DAQmx data index")]
     InvalidDAQmxDataIndex(),
 
@@ -36,6 +43,9 @@ pub enum TdmsError {
     #[error("unknown data type")]
     UnknownDataType(),
 
+    #[error("channel data type {1:?} cannot be read as {0:?}")]
+    DataTypeMismatch(TdmsDataType, TdmsDataType),
+
     #[error("{0} not implemented")]
     NotImplemented(String),
 }
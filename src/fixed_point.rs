@@ -0,0 +1,236 @@
+use crate::channel_iter::ChannelDataIter;
+use crate::data_type::{TDMSValue, TdmsDataType, TdmsNative};
+use crate::segment::MetadataProperty;
+use crate::TdmsError::DataTypeMismatch;
+use crate::{Big, Endianness, General, Little, TdmsError};
+use fixed::traits::Fixed;
+use fixed::types::I64F64;
+use std::io;
+use std::io::{Read, Seek, Write};
+
+/// name of the property holding the total number of bits in a fixed point channel's values
+pub const WORD_LENGTH_PROPERTY: &str = "NI_FixedPoint_WordLength";
+/// name of the property holding the number of integer bits in a fixed point channel's values, which
+/// can be negative or larger than the word length
+pub const INTEGER_WORD_LENGTH_PROPERTY: &str = "NI_FixedPoint_IntegerWordLength";
+/// name of the property holding whether a fixed point channel's values are signed
+pub const SIGNED_PROPERTY: &str = "NI_FixedPoint_Signed";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// `FixedPointEncoding` describes how the integer stored for each fixed point value is interpreted.
+/// A value is its integer multiplied by 2 ^ (`integer_word_length` - `word_length`).
+pub struct FixedPointEncoding {
+    pub signed: bool,
+    pub word_length: u8,
+    pub integer_word_length: i16,
+}
+
+impl FixedPointEncoding {
+    /// `from_properties` reads the encoding from a channel's properties, properties later in the
+    /// list replacing earlier ones with the same name
+    pub fn from_properties<'b, I: IntoIterator<Item = &'b MetadataProperty>>(
+        properties: I,
+    ) -> Result<Self, TdmsError> {
+        let mut signed: Option<bool> = None;
+        let mut word_length: Option<i64> = None;
+        let mut integer_word_length: Option<i64> = None;
+
+        for property in properties {
+            match property.name.as_str() {
+                SIGNED_PROPERTY => signed = Some(property_as_i64(&property.value)? != 0),
+                WORD_LENGTH_PROPERTY => word_length = Some(property_as_i64(&property.value)?),
+                INTEGER_WORD_LENGTH_PROPERTY => {
+                    integer_word_length = Some(property_as_i64(&property.value)?)
+                }
+                _ => (),
+            }
+        }
+
+        let (signed, word_length, integer_word_length) =
+            match (signed, word_length, integer_word_length) {
+                (Some(s), Some(w), Some(i)) => (s, w, i),
+                _ => {
+                    return Err(General(String::from(
+                        "fixed point channel is missing its encoding properties",
+                    )))
+                }
+            };
+
+        let word_length = match u8::try_from(word_length) {
+            Ok(w) if (1..=64).contains(&w) => w,
+            _ => {
+                return Err(General(format!(
+                    "unsupported fixed point word length {}",
+                    word_length
+                )))
+            }
+        };
+
+        Ok(FixedPointEncoding {
+            signed,
+            word_length,
+            integer_word_length: i16::try_from(integer_word_length)?,
+        })
+    }
+
+    /// `fractional_bits` returns the number of bits after the binary point, negative when the value
+    /// is a multiple of a power of two larger than one
+    pub fn fractional_bits(&self) -> i32 {
+        self.word_length as i32 - self.integer_word_length as i32
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// `FixedPoint` is a single fixed point value - the integer it is stored as, only the low
+/// `word_length` bits of which are significant, along with the encoding used to interpret it.
+pub struct FixedPoint {
+    pub bits: u64,
+    pub encoding: FixedPointEncoding,
+}
+
+impl FixedPoint {
+    /// `integer` returns the stored integer, sign extended if the encoding is signed
+    pub fn integer(&self) -> i128 {
+        let unused = 64 - self.encoding.word_length as u32;
+        let bits = self.bits << unused;
+
+        if self.encoding.signed {
+            ((bits as i64) >> unused) as i128
+        } else {
+            (bits >> unused) as i128
+        }
+    }
+
+    /// `to_f64` returns the value as the closest `f64`
+    pub fn to_f64(&self) -> f64 {
+        self.integer() as f64 * 2f64.powi(-self.encoding.fractional_bits())
+    }
+
+    /// `to_fixed` returns the value as a `fixed` crate number such as `I32F32`, or `None` if it
+    /// doesn't fit. Bits below the precision of `F` are truncated
+    pub fn to_fixed<F: Fixed>(&self) -> Option<F> {
+        // I64F64 holds any value with an integer word length up to 63 bits without losing precision
+        let shift = I64F64::FRAC_NBITS as i32 - self.encoding.fractional_bits();
+        let integer = self.integer();
+        let bits = match shift {
+            s if s > 127 && integer != 0 => return None,
+            s if s > 127 => 0,
+            s if s >= 0 => {
+                // shifting back is the only way to notice bits shifted out of the top
+                let bits = integer.checked_shl(s as u32)?;
+                if bits >> s != integer {
+                    return None;
+                }
+                bits
+            }
+            s if s > -128 => integer >> -s,
+            _ => integer >> 127,
+        };
+
+        F::checked_from_num(I64F64::from_bits(bits))
+    }
+}
+
+/// FixedPointWord is the raw integer of a fixed point value. Values take up the size of the
+/// `FixedPoint` data type, the word is held in their low 64 bits
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FixedPointWord(pub(crate) u64);
+
+impl TdmsNative for FixedPointWord {
+    const DATA_TYPE: TdmsDataType = TdmsDataType::FixedPoint(10);
+
+    fn write_to<W: Write>(&self, w: &mut W, endianness: Endianness) -> io::Result<()> {
+        match endianness {
+            Little => {
+                w.write_all(&self.0.to_le_bytes())?;
+                w.write_all(&[0; 2])
+            }
+            Big => {
+                w.write_all(&[0; 2])?;
+                w.write_all(&self.0.to_be_bytes())
+            }
+        }
+    }
+
+    fn read_from(bytes: &[u8], endianness: Endianness) -> Result<Self, TdmsError> {
+        if bytes.len() < 8 {
+            return Err(General(String::from("invalid fixed point value")));
+        }
+
+        let word = match endianness {
+            Little => &bytes[..8],
+            Big => &bytes[bytes.len() - 8..],
+        };
+
+        Ok(FixedPointWord(u64::read_from(word, endianness)?))
+    }
+}
+
+/// `FixedPointIter` iterates through the values of a fixed point channel, decoding each with the
+/// encoding stored in the channel's properties.
+pub struct FixedPointIter<'a, R: Read + Seek> {
+    words: ChannelDataIter<'a, FixedPointWord, R>,
+    encoding: FixedPointEncoding,
+}
+
+impl<'a, R: Read + Seek> FixedPointIter<'a, R> {
+    pub(crate) fn new(
+        words: ChannelDataIter<'a, FixedPointWord, R>,
+        encoding: FixedPointEncoding,
+    ) -> Self {
+        FixedPointIter { words, encoding }
+    }
+
+    pub fn encoding(&self) -> FixedPointEncoding {
+        self.encoding
+    }
+
+    /// `try_next` reads the next value, returning errors instead of stopping on them like
+    /// `ChannelDataIter::try_next`
+    pub fn try_next(&mut self) -> Option<Result<FixedPoint, TdmsError>> {
+        Some(self.words.try_next()?.map(|word| FixedPoint {
+            bits: word.0,
+            encoding: self.encoding,
+        }))
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for FixedPointIter<'a, R> {
+    type Item = FixedPoint;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let word = self.words.next()?;
+
+        Some(FixedPoint {
+            bits: word.0,
+            encoding: self.encoding,
+        })
+    }
+}
+
+impl TDMSValue {
+    /// `to_fixed_point` decodes a `FixedPoint` value with the given encoding. Fixed point properties
+    /// don't carry their encoding, it has to come from elsewhere - typically the channel's encoding
+    /// properties
+    pub fn to_fixed_point(&self, encoding: FixedPointEncoding) -> Result<FixedPoint, TdmsError> {
+        if !FixedPointWord::reads_data_type(self.data_type) {
+            return Err(DataTypeMismatch(FixedPointWord::DATA_TYPE, self.data_type));
+        }
+
+        match &self.value {
+            None => Err(General(String::from("value is empty"))),
+            Some(bytes) => Ok(FixedPoint {
+                bits: FixedPointWord::read_from(bytes, self.endianness)?.0,
+                encoding,
+            }),
+        }
+    }
+}
+
+/// property_as_i64 reads an integer or boolean property, whichever type it was written as
+fn property_as_i64(value: &TDMSValue) -> Result<i64, TdmsError> {
+    match value.data_type {
+        TdmsDataType::Boolean(_) => Ok(value.as_bool()? as i64),
+        _ => value.as_i64(),
+    }
+}
@@ -0,0 +1,363 @@
+use crate::channel_iter::ChannelDataIter;
+use crate::channel_values::ChannelValue;
+use crate::data_type::{TdmsDataType, TdmsNative};
+use crate::digital::DigitalLineIter;
+use crate::fixed_point::{FixedPointEncoding, FixedPointIter, FixedPointWord};
+use crate::reader::SharedReader;
+use crate::segment::{Channel, Segment};
+use crate::TdmsError::{DataTypeMismatch, NotImplemented};
+use crate::{digital_line_scaler, raw_data_type, General, TDMSFile, TdmsError};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufReader;
+
+/// `UnevenLengths` chooses what a `GroupRowIter` does once some of the group's channels have run
+/// out of values while others haven't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnevenLengths {
+    /// stop at the end of the shortest channel
+    Stop,
+    /// carry on to the end of the longest channel, with `None` for channels that have run out
+    PadWithNone,
+    /// return an error in place of the first incomplete row
+    Error,
+}
+
+/// a boxed iterator over one channel's values in a segment, whatever their type
+type ValueSource<'a> = Box<dyn Iterator<Item = Result<ChannelValue, TdmsError>> + 'a>;
+
+/// `GroupRowIter` reads all the channels of a group together, yielding a row holding one value from
+/// each channel for every sample index. It makes a single pass over the file's segments through one
+/// open reader, reading the channels of each segment a row at a time in the order `channels` lists
+/// them. When a channel runs out of values in a segment before the others do, their remaining
+/// values in that segment are held in memory until the following segments catch it up. Iteration
+/// stops after the first error.
+pub struct GroupRowIter<'a> {
+    file: &'a TDMSFile<'a>,
+    reader: SharedReader<File>,
+    channels: Vec<String>,
+    sources: Vec<ChannelSource<'a>>,
+    next_segment: usize,
+    uneven_lengths: UnevenLengths,
+    row: u64,
+    done: bool,
+}
+
+/// `ChannelSource` reads one channel of a group, a segment at a time
+struct ChannelSource<'a> {
+    channel: &'a Channel,
+    decoding: Decoding,
+    // the channel's values in the segment being read, if it has any there
+    values: Option<ValueSource<'a>>,
+    // values read ahead of the rest of the group, returned before any more are read
+    pending: VecDeque<ChannelValue>,
+}
+
+/// `Decoding` is how a channel's raw data turns into values, worked out once from all its segments
+#[derive(Clone, Copy)]
+enum Decoding {
+    DigitalLine(u8),
+    FixedPoint(FixedPointEncoding),
+    Native(TdmsDataType),
+}
+
+impl<'a> ChannelSource<'a> {
+    fn next_value(&mut self) -> Option<Result<ChannelValue, TdmsError>> {
+        match self.pending.pop_front() {
+            Some(value) => Some(Ok(value)),
+            None => self.values.as_mut()?.next(),
+        }
+    }
+}
+
+impl<'a> GroupRowIter<'a> {
+    pub(crate) fn new(
+        file: &'a TDMSFile<'a>,
+        group_path: &str,
+        uneven_lengths: UnevenLengths,
+    ) -> Result<Self, TdmsError> {
+        let mut channels: Vec<String> = vec![];
+        let mut sources: Vec<ChannelSource<'a>> = vec![];
+
+        for (path, channel) in file.channels(group_path) {
+            let vec = file.load_segments(group_path, &path);
+            // channels listed without ever holding data have nothing to contribute to a row
+            if vec.is_empty() {
+                continue;
+            }
+
+            let decoding = if digital_line_scaler(&vec, channel).is_some() {
+                Decoding::DigitalLine(file.digital_line_bit(channel)?)
+            } else {
+                match raw_data_type(&vec, channel) {
+                    TdmsDataType::FixedPoint(_) => {
+                        Decoding::FixedPoint(file.fixed_point_encoding(channel)?)
+                    }
+                    data_type => Decoding::Native(data_type),
+                }
+            };
+
+            sources.push(ChannelSource {
+                channel,
+                decoding,
+                values: None,
+                pending: VecDeque::new(),
+            });
+            channels.push(path);
+        }
+
+        let mut iter = GroupRowIter {
+            file,
+            reader: file.shared_reader()?,
+            channels,
+            sources,
+            next_segment: 0,
+            uneven_lengths,
+            row: 0,
+            done: false,
+        };
+
+        iter.read_segment()?;
+
+        Ok(iter)
+    }
+
+    /// `channels` returns the names of the channels making up each row, in order
+    pub fn channels(&self) -> &[String] {
+        &self.channels
+    }
+
+    /// read_segment moves every channel on to the next segment holding data for any of them,
+    /// keeping the values they hadn't reached yet in the segment before. It returns false once
+    /// there are no segments left.
+    fn read_segment(&mut self) -> Result<bool, TdmsError> {
+        for source in self.sources.iter_mut() {
+            if let Some(values) = source.values.take() {
+                for value in values {
+                    source.pending.push_back(value?);
+                }
+            }
+        }
+
+        while let Some(segment) = self.file.segments.get(self.next_segment) {
+            self.next_segment += 1;
+
+            let mut found = false;
+            for source in self.sources.iter_mut() {
+                let channel = source.channel;
+                match segment.get_channel(channel.group_path.as_str(), channel.path.as_str()) {
+                    // a channel can be listed in a segment only to update its properties
+                    Some(c) if !c.chunk_positions.is_empty() => {
+                        source.values = Some(value_source(
+                            segment,
+                            channel,
+                            source.decoding,
+                            self.reader.clone(),
+                        )?);
+                        found = true;
+                    }
+                    _ => (),
+                }
+            }
+
+            if found {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+impl<'a> Iterator for GroupRowIter<'a> {
+    type Item = Result<Vec<Option<ChannelValue>>, TdmsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let row = loop {
+            let mut row: Vec<Option<ChannelValue>> = Vec::with_capacity(self.sources.len());
+            for source in self.sources.iter_mut() {
+                match source.next_value() {
+                    None => row.push(None),
+                    Some(Ok(value)) => row.push(Some(value)),
+                    Some(Err(e)) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                }
+            }
+
+            if row.iter().all(|v| v.is_some()) {
+                break row;
+            }
+
+            // put back what was read so far, the channels that ran out may go on in a later segment
+            for (source, value) in self.sources.iter_mut().zip(row.iter_mut()) {
+                if let Some(value) = value.take() {
+                    source.pending.push_front(value);
+                }
+            }
+
+            match self.read_segment() {
+                Ok(true) => continue,
+                Ok(false) => (),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+
+            // every segment has been read, so the row is as complete as it will get
+            break self
+                .sources
+                .iter_mut()
+                .map(|source| source.pending.pop_front())
+                .collect();
+        };
+
+        let ended = row.iter().filter(|v| v.is_none()).count();
+        if ended == row.len() {
+            self.done = true;
+            return None;
+        }
+
+        if ended > 0 {
+            match self.uneven_lengths {
+                UnevenLengths::PadWithNone => (),
+                UnevenLengths::Stop => {
+                    self.done = true;
+                    return None;
+                }
+                UnevenLengths::Error => {
+                    self.done = true;
+                    let ended: Vec<&str> = self
+                        .channels
+                        .iter()
+                        .zip(&row)
+                        .filter(|(_, v)| v.is_none())
+                        .map(|(c, _)| c.as_str())
+                        .collect();
+
+                    return Some(Err(General(format!(
+                        "channels {:?} have only {} values, fewer than the rest of the group",
+                        ended, self.row
+                    ))));
+                }
+            }
+        }
+
+        self.row += 1;
+        Some(Ok(row))
+    }
+}
+
+/// value_source builds the iterator reading a channel's values in one segment, picking it from the
+/// channel's decoding the same way `TDMSFile::any_channel_data` does
+fn value_source<'a>(
+    segment: &'a Segment,
+    channel: &'a Channel,
+    decoding: Decoding,
+    reader: SharedReader<File>,
+) -> Result<ValueSource<'a>, TdmsError> {
+    let data_type = match decoding {
+        Decoding::DigitalLine(bit) => {
+            let bytes = segment_data::<u8>(segment, channel, reader)?;
+            let mut lines = DigitalLineIter::new(bytes, bit);
+
+            return Ok(Box::new(std::iter::from_fn(move || {
+                Some(lines.try_next()?.map(ChannelValue::Boolean))
+            })));
+        }
+        Decoding::FixedPoint(encoding) => {
+            let words = segment_data::<FixedPointWord>(segment, channel, reader)?;
+            let mut values = FixedPointIter::new(words, encoding);
+
+            return Ok(Box::new(std::iter::from_fn(move || {
+                Some(values.try_next()?.map(ChannelValue::FixedPoint))
+            })));
+        }
+        Decoding::Native(data_type) => data_type,
+    };
+
+    let source = match data_type {
+        TdmsDataType::I8(_) => boxed(segment_data(segment, channel, reader)?, ChannelValue::I8),
+        TdmsDataType::I16(_) => boxed(segment_data(segment, channel, reader)?, ChannelValue::I16),
+        TdmsDataType::I32(_) => boxed(segment_data(segment, channel, reader)?, ChannelValue::I32),
+        TdmsDataType::I64(_) => boxed(segment_data(segment, channel, reader)?, ChannelValue::I64),
+        TdmsDataType::U8(_) => boxed(segment_data(segment, channel, reader)?, ChannelValue::U8),
+        TdmsDataType::U16(_) => boxed(segment_data(segment, channel, reader)?, ChannelValue::U16),
+        TdmsDataType::U32(_) => boxed(segment_data(segment, channel, reader)?, ChannelValue::U32),
+        TdmsDataType::U64(_) => boxed(segment_data(segment, channel, reader)?, ChannelValue::U64),
+        TdmsDataType::SingleFloat(_) | TdmsDataType::SingleFloatWithUnit(_) => boxed(
+            segment_data(segment, channel, reader)?,
+            ChannelValue::SingleFloat,
+        ),
+        TdmsDataType::DoubleFloat(_) | TdmsDataType::DoubleFloatWithUnit(_) => boxed(
+            segment_data(segment, channel, reader)?,
+            ChannelValue::DoubleFloat,
+        ),
+        TdmsDataType::ExtendedFloat(_) | TdmsDataType::ExtendedFloatWithUnit(_) => boxed(
+            segment_data(segment, channel, reader)?,
+            ChannelValue::ExtendedFloat,
+        ),
+        TdmsDataType::String => boxed(
+            segment_data(segment, channel, reader)?,
+            ChannelValue::String,
+        ),
+        TdmsDataType::Boolean(_) => boxed(
+            segment_data(segment, channel, reader)?,
+            ChannelValue::Boolean,
+        ),
+        TdmsDataType::TimeStamp(_) => boxed(
+            segment_data(segment, channel, reader)?,
+            ChannelValue::TimeStamp,
+        ),
+        TdmsDataType::ComplexSingleFloat(_) => boxed(
+            segment_data(segment, channel, reader)?,
+            ChannelValue::ComplexSingleFloat,
+        ),
+        TdmsDataType::ComplexDoubleFloat(_) => boxed(
+            segment_data(segment, channel, reader)?,
+            ChannelValue::ComplexDoubleFloat,
+        ),
+        data_type => {
+            return Err(NotImplemented(format!(
+                "reading {:?} channel data",
+                data_type
+            )))
+        }
+    };
+
+    Ok(source)
+}
+
+/// segment_data opens an iterator over a channel's raw data in one segment, checking the data type
+/// the same way `TDMSFile::channel_data_with_reader` does
+fn segment_data<'a, T: TdmsNative>(
+    segment: &'a Segment,
+    channel: &'a Channel,
+    reader: SharedReader<File>,
+) -> Result<ChannelDataIter<'a, T, SharedReader<File>>, TdmsError> {
+    let data_type = raw_data_type(&[segment], channel);
+    if !T::reads_data_type(data_type) {
+        return Err(DataTypeMismatch(T::DATA_TYPE, data_type));
+    }
+
+    ChannelDataIter::new(
+        vec![segment],
+        channel,
+        BufReader::with_capacity(4096, reader),
+    )
+}
+
+fn boxed<'a, T: TdmsNative + 'a>(
+    mut iter: ChannelDataIter<'a, T, SharedReader<File>>,
+    value: fn(T) -> ChannelValue,
+) -> ValueSource<'a> {
+    Box::new(std::iter::from_fn(move || {
+        Some(iter.try_next()?.map(value))
+    }))
+}
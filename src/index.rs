@@ -0,0 +1,83 @@
+use crate::segment::Segment;
+use crate::TdmsError;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// tag that starts every segment of a `.tdms_index` file, in place of the usual `TDSm`
+const INDEX_TAG: &[u8; 4] = b"TDSh";
+
+/// `index_path` returns the path of the `.tdms_index` companion file for a TDMS file, LabVIEW names
+/// it by appending `_index` to the file's name - `data.tdms` becomes `data.tdms_index`
+pub fn index_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push("_index");
+
+    PathBuf::from(name)
+}
+
+/// `read_index` builds a TDMS file's segments from its index file without touching the file itself.
+/// Returns `None` if the index file doesn't exist or if it doesn't describe a file of `file_len`
+/// bytes, which happens when the file was written to or truncated after the index.
+pub(crate) fn read_index(
+    index_path: &Path,
+    file_len: u64,
+) -> Result<Option<Vec<Segment>>, TdmsError> {
+    let file = match File::open(index_path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(TdmsError::from(e)),
+    };
+
+    let index_len = file.metadata()?.len();
+    let mut reader = BufReader::with_capacity(4096, file);
+    let mut segments: Vec<Segment> = vec![];
+    // position of the current segment in the TDMS file, not the index
+    let mut segment_start_pos: u64 = 0;
+    let mut index_pos: u64 = 0;
+
+    while index_pos < index_len {
+        let segment =
+            Segment::from_index(&mut reader, segments.last(), segment_start_pos, file_len)?;
+
+        // segment ends are clamped to the file's length, only a complete segment is sure to still
+        // be entirely in the file - an index written before the file was truncated is stale too
+        if !segment.is_complete() {
+            return Ok(None);
+        }
+
+        // index segments contain the lead in and metadata only, raw_data_offset covers both
+        index_pos += segment.lead_in.raw_data_offset;
+        reader.seek(SeekFrom::Start(index_pos))?;
+
+        segment_start_pos = segment.end_pos;
+        segments.push(segment);
+    }
+
+    if segment_start_pos != file_len {
+        return Ok(None);
+    }
+
+    Ok(Some(segments))
+}
+
+/// `write_index` writes the `.tdms_index` file for the TDMS file at `path`, given its segments. The
+/// lead in and metadata of every segment are copied from the file with only the tag replaced.
+pub(crate) fn write_index(path: &Path, segments: &[Segment]) -> Result<(), TdmsError> {
+    let mut reader = BufReader::with_capacity(4096, File::open(path)?);
+    let mut writer = BufWriter::new(File::create(index_path(path))?);
+
+    for segment in segments {
+        let mut buf = vec![0; segment.lead_in.raw_data_offset as usize];
+
+        reader.seek(SeekFrom::Start(segment.start_pos))?;
+        reader.read_exact(&mut buf)?;
+
+        buf[0..4].copy_from_slice(INDEX_TAG);
+        writer.write_all(&buf)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
@@ -7,12 +7,34 @@
 //! - Read both standard and big endian encoded files
 //! - Read files with DAQmx data and data indices
 //! - Read all segments in file, along with their groups and channels (per segment only)
+//! - Use the `.tdms_index` file, when present and up to date, to read segments without seeking through the file
+//! - Follow files that are still being written with `TDMSFile::follow` and `refresh`, resuming channel iterators where they stopped
+//! - Recover the readable segments of truncated or corrupted files with `TDMSFile::recover`, along with a report of what was skipped
 //! - Read all raw data contained in all segments in file (as a `Vec<u8>` only at the present time)
+//! - Read channel data as native Rust values with `channel_data::<T>()`, which checks `T` against the channel's data type
+//! - Read channel data without knowing its data type ahead of time with `any_channel_data`, returning a `ChannelValues` enum
+//! - Read complex channels as `TdmsComplex` values, or as `num_complex::Complex` with the `num-complex` feature enabled
+//! - Decode 80-bit extended precision channels and properties, either losslessly as `Extended` or rounded to `f64`
+//! - Decode `FixedPoint` channels and properties using their word length, integer word length and signedness, as `f64` or a `fixed` number
+//! - Read DAQmx raw data channels, decoding each channel's values from the shared raw buffers using its format changing scaler
+//! - Read DAQmx digital line channels as per-line booleans with `digital_line_data`, or pack several lines into port values with `digital_port_data`
+//! - Apply `NI_Scale` properties (linear, polynomial, table, thermocouple, RTD, strain and reciprocal scales, chained in order) with `channel_data_scaled`, yielding values in engineering units
+//! - Read a channel's waveform time axis from its `wf_` properties with `waveform`, giving the time of any sample and pairing values with their timestamps
+//! - Convert `TdmsTimestamp` to and from `SystemTime`, `Duration` and Unix nanoseconds, or `chrono` and `time` types with the `chrono` and `time` features enabled. Timestamps are ordered, can be offset by a `Duration` and display as ISO-8601
+//! - Read property values directly with `TDMSValue::as_f64`, `as_i64`, `as_str`, `as_timestamp` and `as_bool`, `TryFrom<&TDMSValue>` for native types, or format them with `Display`
+//! - Read the file's, a group's or a channel's properties merged across every segment, later values replacing earlier ones, with `file_properties`, `group_properties` and `channel_properties`
+//! - Parse and build object paths such as `/'group'/'channel'` with `ObjectPath`, handling names containing `/` or escaped `''` quotes
+//! - Iterate channel data as `Result`s with `try_iter` or `try_next`, telling read errors, corrupt data (`CorruptChannelData`) and a truncated file (`TruncatedChannelData`) apart from the end of the channel
+//! - Read a channel's values in bulk with `read_all` or `read_into` a slice, copying contiguous data a chunk at a time
+//! - Jump to any sample of a channel with `seek_to_sample`, `nth` or `read_range`, which work out its position from the chunk sizes instead of reading the values before it
+//! - Count a channel's values with `channel_length` and list where they are stored with `channel_chunks` (segment, file offset, value range and stride), without reading any raw data
+//! - Read the values of a waveform channel taken between two timestamps with `read_time_range`, following channels whose `wf_` properties change between segments
+//! - Read every channel of a group together, row by row, through a single open file with `group_rows`, stopping, padding with `None` or failing when channels have different lengths
+//! - Read any number of channels at once through a single open file, shared between iterators with `SharedReader`, or read a channel from your own `Read + Seek` source with `channel_data_with_reader`
+//! - Write new TDMS files containing groups, channels, properties and raw data, or append segments to existing files, using `TdmsWriter`
 //! - Logging using the `log` api - users of the library must choose and initialize the implementation, such as `env-logger`
 //!
 //! ### Planned Features
-//! - Iterators for each channel type, return native Rust values from encoded data channels
-//! - DAQmx data channel iterator support
 //! - Searching on string channels
 //!
 //!
@@ -43,12 +65,11 @@
 //!         let mut i = 0;
 //!         for (_, channel) in channels {
 //!             // once you know the channel's full path (group + channel) you can ask for the full
-//!             // channel object. In order to fetch a channel you must call the proper channel func
-//!             // depending on your data type. Currently this feature is unimplemented but the method
-//!             // of calling this is set down for future changes
+//!             // channel object. In order to fetch a channel's data you must ask for the native type
+//!             // matching its data type, asking for any other type returns an error
 //!             let full_channel = match channel.data_type {
 //!                 // the returned full channel is an iterator over raw data
-//!                 TdmsDataType::DoubleFloat(_) => file.channel_data_double_float(channel),
+//!                 TdmsDataType::DoubleFloat(_) => file.channel_data::<f64>(channel),
 //!                 _ => {
 //!                     panic!("{}", "channel for data type unimplemented")
 //!                 }
@@ -81,32 +102,58 @@
 //! [MIT](https://choosealicense.com/licenses/mit/)
 //!
 use indexmap::{IndexMap, IndexSet};
+use log::warn;
 use std::fs;
 use std::fs::File;
-use std::io::{BufReader, Seek, SeekFrom};
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom};
 use std::path::Path;
+use std::sync::OnceLock;
 
 pub mod error;
 use crate::channel_iter::ChannelDataIter;
-use crate::data_type::TdmsTimestamp;
+use crate::channel_values::ChannelValues;
+use crate::data_type::{Extended, TDMSValue, TdmsComplex, TdmsDataType, TdmsNative, TdmsTimestamp};
+use crate::digital::{DigitalLineIter, DigitalPortIter};
+use crate::fixed_point::{FixedPointEncoding, FixedPointIter, FixedPointWord};
+use crate::group_rows::{GroupRowIter, UnevenLengths};
+use crate::object_path::ObjectPath;
+use crate::reader::SharedReader;
+use crate::scaling::{RawValue, ScaledIter, Scaling};
+use crate::waveform::Waveform;
 use crate::TdmsError::{
-    General, InvalidDAQmxDataIndex, InvalidSegment, StringConversionError, UnknownDataType,
+    DataTypeMismatch, General, InvalidDAQmxDataIndex, InvalidSegment, NotImplemented, ReadError,
+    StringConversionError, UnknownDataType,
 };
 pub use error::TdmsError;
+use recovery::{SkipReason, SkippedRegion};
 use segment::Endianness::{Big, Little};
-use segment::{Channel, Endianness, Segment};
+use segment::{Channel, ChannelChunk, DigitalLineScaler, Endianness, MetadataProperty, Segment};
 
 pub mod channel_iter;
+pub mod channel_values;
 pub mod data_type;
+pub mod digital;
+pub mod fixed_point;
+pub mod group_rows;
+pub mod index;
+pub mod object_path;
+pub mod reader;
+pub mod recovery;
+pub mod scaling;
 pub mod segment;
 #[cfg(test)]
 mod tests;
+mod timestamp;
+pub mod waveform;
+pub mod writer;
 
 #[derive(Debug, Clone)]
 /// `TDDMSFile` represents all `segments` of a TDMS file in the order in which they were read.
 pub struct TDMSFile<'a> {
     pub segments: Vec<Segment>,
     path: &'a Path,
+    // opened the first time channel data is read, then lent to every channel iterator
+    reader: OnceLock<SharedReader<File>>,
 }
 
 impl<'a> TDMSFile<'a> {
@@ -114,26 +161,206 @@ impl<'a> TDMSFile<'a> {
     /// the entire file into working memory.
     pub fn from_path(path: &'a Path) -> Result<Self, TdmsError> {
         let metadata = fs::metadata(path)?;
-        let file = File::open(path)?;
-        let mut reader = BufReader::with_capacity(4096, file);
+
+        // the index file holds only the lead in and metadata of each segment, reading it is much
+        // faster than seeking through the file itself. Missing or stale index files are ignored
+        match index::read_index(&index::index_path(path), metadata.len()) {
+            Ok(Some(segments)) => {
+                return Ok(TDMSFile {
+                    segments,
+                    path,
+                    reader: OnceLock::new(),
+                })
+            }
+            Ok(None) => (),
+            Err(e) => warn!("unable to read index file, falling back to file: {:?}", e),
+        }
+
+        TDMSFile::from_path_without_index(path)
+    }
+
+    /// from_path_without_index reads every segment from the file itself, ignoring any index file
+    pub(crate) fn from_path_without_index(path: &'a Path) -> Result<Self, TdmsError> {
+        let mut file = TDMSFile {
+            segments: vec![],
+            path,
+            reader: OnceLock::new(),
+        };
+        file.read_segments(false)?;
+
+        Ok(file)
+    }
+
+    /// `follow` opens a file that is still being written to, such as a file LabVIEW is logging to.
+    /// Unlike `from_path` the index file is never used, and a final segment that hasn't been fully
+    /// written yet is ignored rather than returned as an error. Call `refresh` to read segments
+    /// written after the file was opened.
+    pub fn follow(path: &'a Path) -> Result<Self, TdmsError> {
+        let mut file = TDMSFile {
+            segments: vec![],
+            path,
+            reader: OnceLock::new(),
+        };
+        file.refresh()?;
+
+        Ok(file)
+    }
+
+    /// `refresh` reads any segments written to the file since it was opened or last refreshed,
+    /// returning how many were read. A final segment that was still being written is read again so
+    /// that its new raw data is picked up. Existing `ChannelDataIter`s must be dropped before
+    /// refreshing, record their `position` first and `resume` new iterators from it.
+    pub fn refresh(&mut self) -> Result<usize, TdmsError> {
+        self.read_segments(true)
+    }
+
+    /// `recover` reads as much of a damaged file as possible, such as one left behind by a crashed
+    /// logger. Every valid segment before the damage is kept and a truncated final segment keeps its
+    /// complete chunks of raw data. When `resync` is set, the rest of the file is scanned for the
+    /// next `TDSm` tag and reading carries on from there - segments found this way that don't start
+    /// a new object list rely on the last segment read before the damage. Returns the file along
+    /// with every region of it that was skipped.
+    pub fn recover(path: &'a Path, resync: bool) -> Result<(Self, Vec<SkippedRegion>), TdmsError> {
+        let file_len = fs::metadata(path)?.len();
+        let mut reader = BufReader::with_capacity(4096, File::open(path)?);
         let mut segments: Vec<Segment> = vec![];
-        let mut i = 0;
+        let mut skipped: Vec<SkippedRegion> = vec![];
+        // start of the damaged region currently being skipped, along with the reason
+        let mut damaged: Option<(u64, SkipReason)> = None;
+        let mut pos = 0;
+
+        while pos < file_len {
+            let segment = if file_len - pos < 28 {
+                Err(General(String::from("not enough bytes left for a lead in")))
+            } else {
+                reader.seek(SeekFrom::Start(pos))?;
+                Segment::new(&mut reader, segments.last())
+            };
 
-        loop {
-            let previous_segment = if i == 0 { None } else { segments.get(i - 1) };
-            let segment = Segment::new(&mut reader, previous_segment)?;
+            let mut segment = match segment {
+                Ok(s) => s,
+                Err(e) => {
+                    if damaged.is_none() {
+                        damaged = Some((pos, SkipReason::InvalidSegment(e.to_string())));
+                    }
 
-            if segment.end_pos == metadata.len() {
-                segments.push(segment);
-                break;
+                    if !resync {
+                        break;
+                    }
+
+                    match recovery::find_segment_tag(&mut reader, pos + 1)? {
+                        None => break,
+                        Some(next) => pos = next,
+                    }
+
+                    continue;
+                }
+            };
+
+            if let Some((start_pos, reason)) = damaged.take() {
+                skipped.push(SkippedRegion {
+                    start_pos,
+                    end_pos: pos,
+                    reason,
+                });
+            }
+
+            let mut end_pos = segment.end_pos;
+            if !segment.is_complete() {
+                // an incomplete segment runs to the end of the file, unless another segment was
+                // started after it was abandoned
+                if resync {
+                    let raw_data_start = segment.start_pos + segment.lead_in.raw_data_offset;
+                    if let Some(next) = recovery::find_segment_tag(&mut reader, raw_data_start)? {
+                        end_pos = end_pos.min(next);
+                    }
+                }
+
+                let truncated_end_pos = segment.truncate_at(end_pos);
+
+                if truncated_end_pos < end_pos {
+                    skipped.push(SkippedRegion {
+                        start_pos: truncated_end_pos,
+                        end_pos,
+                        reason: SkipReason::PartialChunk,
+                    });
+                }
             }
 
-            reader.seek(SeekFrom::Start(segment.end_pos))?;
+            pos = end_pos;
             segments.push(segment);
-            i += 1;
         }
 
-        return Ok(TDMSFile { segments, path });
+        if let Some((start_pos, reason)) = damaged {
+            skipped.push(SkippedRegion {
+                start_pos,
+                end_pos: file_len,
+                reason,
+            });
+        }
+
+        Ok((
+            TDMSFile {
+                segments,
+                path,
+                reader: OnceLock::new(),
+            },
+            skipped,
+        ))
+    }
+
+    /// read_segments reads segments from the end of the last complete segment until the end of the
+    /// file. When `allow_partial` is set, a segment whose lead in or metadata has not been
+    /// completely written yet marks the end of the file instead of causing an error
+    fn read_segments(&mut self, allow_partial: bool) -> Result<usize, TdmsError> {
+        let file_len = fs::metadata(self.path)?.len();
+        let file = File::open(self.path)?;
+        let mut reader = BufReader::with_capacity(4096, file);
+
+        let mut pos = match self.segments.last() {
+            None => 0,
+            Some(segment) if !segment.is_complete() => {
+                let start_pos = segment.start_pos;
+                self.segments.pop();
+                start_pos
+            }
+            Some(segment) => segment.end_pos,
+        };
+
+        let mut read = 0;
+
+        while pos < file_len {
+            if allow_partial && file_len - pos < 28 {
+                break;
+            }
+
+            reader.seek(SeekFrom::Start(pos))?;
+
+            let segment = match Segment::new(&mut reader, self.segments.last()) {
+                Ok(s) => s,
+                Err(ReadError(e)) if allow_partial && e.kind() == ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            };
+
+            let complete = segment.is_complete();
+            pos = segment.end_pos;
+            self.segments.push(segment);
+            read += 1;
+
+            // an incomplete segment runs to the end of the file
+            if !complete {
+                break;
+            }
+        }
+
+        Ok(read)
+    }
+
+    /// write_index writes a `.tdms_index` file next to the TDMS file, allowing later calls to
+    /// `from_path` to read the segments without seeking through the file. An existing index file is
+    /// overwritten
+    pub fn write_index(&self) -> Result<(), TdmsError> {
+        index::write_index(self.path, &self.segments)
     }
 
     /// groups returns all possible groups throughout the file
@@ -171,221 +398,611 @@ impl<'a> TDMSFile<'a> {
         return map;
     }
 
+    /// `channel_length` returns the number of values a channel has across every segment, worked
+    /// out from the segments' metadata without reading any raw data
+    pub fn channel_length(&self, group_path: &str, path: &str) -> u64 {
+        match self.channel_chunks(group_path, path).last() {
+            None => 0,
+            Some(chunk) => chunk.samples.end,
+        }
+    }
+
+    /// `channel_chunks` lists the chunks of a channel's raw data in file order, giving where each
+    /// starts and which of the channel's values it holds
+    pub fn channel_chunks(&self, group_path: &str, path: &str) -> Vec<ChannelChunk> {
+        let mut chunks: Vec<ChannelChunk> = vec![];
+        let mut sample: u64 = 0;
+
+        for (segment_index, segment) in self.segments.iter().enumerate() {
+            let channel = match segment.get_channel(group_path, path) {
+                None => continue,
+                Some(c) => c,
+            };
+
+            for (i, positions) in channel.chunk_positions.iter().enumerate() {
+                let values = channel.values_in_chunk(i);
+
+                chunks.push(ChannelChunk {
+                    segment_index,
+                    offset: positions.0,
+                    samples: sample..sample + values,
+                    stride: channel.stride(),
+                });
+
+                sample += values;
+            }
+        }
+
+        chunks
+    }
+
+    /// `file_properties` returns the properties of the file's root object. Properties can be set
+    /// again in later segments, the latest value of each is returned
+    pub fn file_properties(&self) -> IndexMap<String, &TDMSValue> {
+        property_values(self.object_properties(&ObjectPath::Root))
+    }
+
+    /// `group_properties` returns the properties of a group, the latest value of each
+    pub fn group_properties(&self, group_path: &str) -> IndexMap<String, &TDMSValue> {
+        property_values(self.object_properties(&ObjectPath::group(group_path)))
+    }
+
+    /// `channel_properties` returns the properties of a channel, the latest value of each. Unlike
+    /// the properties of the `Channel` returned by `channels`, which are only those listed in a
+    /// single segment, these include properties from every segment the channel is listed in
+    pub fn channel_properties(&self, group_path: &str, path: &str) -> IndexMap<String, &TDMSValue> {
+        property_values(self.object_properties(&ObjectPath::channel(group_path, path)))
+    }
+
+    /// object_properties merges the properties of an object from every segment listing it, later
+    /// values replacing earlier ones but keeping the order properties were first seen in
+    fn object_properties(&self, object_path: &ObjectPath) -> IndexMap<&str, &MetadataProperty> {
+        let mut map: IndexMap<&str, &MetadataProperty> = IndexMap::new();
+        let object_path = object_path.to_string();
+
+        let objects = self
+            .segments
+            .iter()
+            .filter_map(|s| s.metadata.as_ref())
+            .flat_map(|m| m.objects.iter())
+            .filter(|o| o.object_path == object_path);
+
+        for object in objects {
+            for property in &object.properties {
+                map.insert(property.name.as_str(), property);
+            }
+        }
+
+        map
+    }
+
+    /// `channel_data` returns an iterator over a channel's raw data as the native Rust type `T`, such
+    /// as `f64` for `DoubleFloat` channels or `String` for `String` channels. Asking for a type the
+    /// channel's data isn't stored as returns a `DataTypeMismatch` error instead of reinterpreting
+    /// the raw bytes. DAQmx channels yield the unscaled values of their first format changing
+    /// scaler, extracted from the segment's shared raw buffers. Every iterator reads through the
+    /// same open file, see `shared_reader`
+    pub fn channel_data<T: TdmsNative>(
+        &self,
+        channel: &'a Channel,
+    ) -> Result<ChannelDataIter<'_, T, SharedReader<File>>, TdmsError> {
+        self.channel_data_with_reader(channel, self.shared_reader()?)
+    }
+
+    /// `channel_data_with_reader` works like `channel_data`, reading the channel's data from
+    /// `reader` instead of the file's shared reader - e.g. a reader over a copy of the file held in
+    /// memory. `reader` must hold the same bytes as the file at the same positions
+    pub fn channel_data_with_reader<T: TdmsNative, R: Read + Seek>(
+        &self,
+        channel: &'a Channel,
+        reader: R,
+    ) -> Result<ChannelDataIter<'_, T, R>, TdmsError> {
+        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
+
+        let data_type = raw_data_type(&vec, channel);
+        if !T::reads_data_type(data_type) {
+            return Err(DataTypeMismatch(T::DATA_TYPE, data_type));
+        }
+
+        ChannelDataIter::new(vec, channel, BufReader::with_capacity(4096, reader))
+    }
+
+    /// `shared_reader` returns a handle to the file opened for reading channel data, opening it on
+    /// first use. All handles share one file descriptor, each keeping its own position, so any
+    /// number of channel iterators can be open at once
+    pub fn shared_reader(&self) -> Result<SharedReader<File>, TdmsError> {
+        if let Some(reader) = self.reader.get() {
+            return Ok(reader.clone());
+        }
+
+        let reader = SharedReader::new(File::open(self.path)?);
+        // another thread may have opened the file first, in which case theirs is kept
+        Ok(self.reader.get_or_init(|| reader).clone())
+    }
+
+    /// `any_channel_data` reads all of a channel's raw data into the `ChannelValues` variant matching
+    /// its data type, for when the data type isn't known at compile time
+    pub fn any_channel_data(&self, channel: &'a Channel) -> Result<ChannelValues, TdmsError> {
+        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
+
+        if digital_line_scaler(&vec, channel).is_some() {
+            return Ok(ChannelValues::Boolean(
+                self.digital_line_data(channel)?.collect(),
+            ));
+        }
+
+        let values = match raw_data_type(&vec, channel) {
+            TdmsDataType::Void => ChannelValues::Void,
+            TdmsDataType::I8(_) => ChannelValues::I8(self.channel_data(channel)?.collect()),
+            TdmsDataType::I16(_) => ChannelValues::I16(self.channel_data(channel)?.collect()),
+            TdmsDataType::I32(_) => ChannelValues::I32(self.channel_data(channel)?.collect()),
+            TdmsDataType::I64(_) => ChannelValues::I64(self.channel_data(channel)?.collect()),
+            TdmsDataType::U8(_) => ChannelValues::U8(self.channel_data(channel)?.collect()),
+            TdmsDataType::U16(_) => ChannelValues::U16(self.channel_data(channel)?.collect()),
+            TdmsDataType::U32(_) => ChannelValues::U32(self.channel_data(channel)?.collect()),
+            TdmsDataType::U64(_) => ChannelValues::U64(self.channel_data(channel)?.collect()),
+            TdmsDataType::SingleFloat(_) | TdmsDataType::SingleFloatWithUnit(_) => {
+                ChannelValues::SingleFloat(self.channel_data(channel)?.collect())
+            }
+            TdmsDataType::DoubleFloat(_) | TdmsDataType::DoubleFloatWithUnit(_) => {
+                ChannelValues::DoubleFloat(self.channel_data(channel)?.collect())
+            }
+            TdmsDataType::ExtendedFloat(_) | TdmsDataType::ExtendedFloatWithUnit(_) => {
+                ChannelValues::ExtendedFloat(self.channel_data(channel)?.collect())
+            }
+            TdmsDataType::String => ChannelValues::String(self.channel_data(channel)?.collect()),
+            TdmsDataType::Boolean(_) => {
+                ChannelValues::Boolean(self.channel_data(channel)?.collect())
+            }
+            TdmsDataType::TimeStamp(_) => {
+                ChannelValues::TimeStamp(self.channel_data(channel)?.collect())
+            }
+            TdmsDataType::FixedPoint(_) => {
+                ChannelValues::FixedPoint(self.channel_data_fixed_point(channel)?.collect())
+            }
+            TdmsDataType::ComplexSingleFloat(_) => {
+                ChannelValues::ComplexSingleFloat(self.channel_data(channel)?.collect())
+            }
+            TdmsDataType::ComplexDoubleFloat(_) => {
+                ChannelValues::ComplexDoubleFloat(self.channel_data(channel)?.collect())
+            }
+            data_type => {
+                return Err(NotImplemented(format!(
+                    "reading {:?} channel data",
+                    data_type
+                )))
+            }
+        };
+
+        Ok(values)
+    }
+
+    /// `group_rows` returns an iterator reading every channel of a group together, one row of
+    /// values per sample index, through a single open file. `uneven_lengths` chooses what happens
+    /// once some channels run out of values before others
+    pub fn group_rows(
+        &self,
+        group_path: &str,
+        uneven_lengths: UnevenLengths,
+    ) -> Result<GroupRowIter<'_>, TdmsError> {
+        GroupRowIter::new(self, group_path, uneven_lengths)
+    }
+
     /// returns a channel who's type is the native rust type equivalent to TdmsDoubleFloat, in this
     /// case `f64` - the channel implements Iterator and using said iterator will let you move through
-    /// the channel's raw data if any exists
+    /// the channel's raw data if any exists. Shorthand for `channel_data::<f64>`
     pub fn channel_data_double_float(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<f64, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
-
-        return ChannelDataIter::new(vec, channel, reader);
+    ) -> Result<ChannelDataIter<f64, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
+    /// shorthand for `channel_data::<f32>`
     pub fn channel_data_single_float(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<f32, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
+    ) -> Result<ChannelDataIter<f32, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
+    }
 
-        return ChannelDataIter::new(vec, channel, reader);
+    /// shorthand for `channel_data::<Extended>`, use `channel_data::<f64>` to read extended precision
+    /// channels rounded to doubles instead
+    pub fn channel_data_extended_float(
+        &self,
+        channel: &'a Channel,
+    ) -> Result<ChannelDataIter<'_, Extended, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
-    pub fn channel_data_complex_double_float(
+    /// `channel_data_fixed_point` returns an iterator over a `FixedPoint` channel's values, decoded
+    /// using the word length, integer word length and signedness stored in the channel's properties
+    pub fn channel_data_fixed_point(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<f64, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
+    ) -> Result<FixedPointIter<'_, SharedReader<File>>, TdmsError> {
+        let words = self.channel_data::<FixedPointWord>(channel)?;
 
-        return ChannelDataIter::new(vec, channel, reader);
+        Ok(FixedPointIter::new(
+            words,
+            self.fixed_point_encoding(channel)?,
+        ))
     }
 
-    pub fn channel_data_complex_single_float(
+    /// fixed_point_encoding reads the encoding of a fixed point channel from its properties
+    pub(crate) fn fixed_point_encoding(
+        &self,
+        channel: &Channel,
+    ) -> Result<FixedPointEncoding, TdmsError> {
+        // the encoding properties are normally written once, when the channel is first listed
+        let properties = self.object_properties(&ObjectPath::channel(
+            channel.group_path.as_str(),
+            channel.path.as_str(),
+        ));
+
+        FixedPointEncoding::from_properties(properties.into_values())
+    }
+
+    /// `channel_data_scaled` returns an iterator over an integer or floating point channel's values
+    /// in engineering units, applying the scales described by the channel's `NI_Scale` properties
+    /// to its raw values. Channels without scales yield their raw values as `f64`
+    pub fn channel_data_scaled(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<f32, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
+    ) -> Result<ScaledIter<'_, SharedReader<File>>, TdmsError> {
+        let raw = self.channel_data::<RawValue>(channel)?;
 
-        return ChannelDataIter::new(vec, channel, reader);
+        // scaling properties are normally written once, when the channel is first listed
+        let properties = self.object_properties(&ObjectPath::channel(
+            channel.group_path.as_str(),
+            channel.path.as_str(),
+        ));
+        let scaling = Scaling::from_properties(properties.into_values())?;
+
+        Ok(ScaledIter::new(raw, scaling))
     }
 
-    pub fn channel_data_double_float_unit(
+    /// `waveform` returns the time axis of a channel written from a LabVIEW waveform, read from its
+    /// `wf_` properties. Use `Waveform::with_values` to pair the channel's values with their times
+    pub fn waveform(&self, channel: &'a Channel) -> Result<Waveform, TdmsError> {
+        // waveform properties can be written after the data, e.g. once acquisition has finished
+        let properties = self.object_properties(&ObjectPath::channel(
+            channel.group_path.as_str(),
+            channel.path.as_str(),
+        ));
+
+        Waveform::from_properties(properties.into_values())
+    }
+
+    /// `read_time_range` reads the values of a waveform channel taken from `start` up to but not
+    /// including `end`, each paired with its time. A segment setting new `wf_` properties for the
+    /// channel starts a new waveform at the channel's first value in that segment, so channels
+    /// written as several waveforms are read against the right time axis
+    pub fn read_time_range<T: TdmsNative>(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<f64, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
+        start: TdmsTimestamp,
+        end: TdmsTimestamp,
+    ) -> Result<Vec<(TdmsTimestamp, T)>, TdmsError> {
+        let group_path = channel.group_path.as_str();
+        let path = channel.path.as_str();
+        let object_path = ObjectPath::channel(group_path, path).to_string();
+
+        let chunks = self.channel_chunks(group_path, path);
+        let length = self.channel_length(group_path, path);
+
+        // the first sample of each waveform making up the channel
+        let mut waveforms: Vec<(u64, Waveform)> = vec![];
+        let mut properties = vec![];
+
+        for (index, segment) in self.segments.iter().enumerate() {
+            let objects = segment
+                .metadata
+                .iter()
+                .flat_map(|m| m.objects.iter())
+                .filter(|o| o.object_path == object_path);
+
+            let mut changed = false;
+            for object in objects {
+                for property in &object.properties {
+                    changed |= property.name.starts_with("wf_");
+                    properties.push(property);
+                }
+            }
+
+            if !changed {
+                continue;
+            }
+
+            let waveform = Waveform::from_properties(properties.iter().copied())?;
+            if !waveform.increment.is_finite() || waveform.increment <= 0.0 {
+                return Err(General(format!(
+                    "waveform increment {} is not positive",
+                    waveform.increment
+                )));
+            }
+
+            // waveform properties written before any of the channel's data apply from its start
+            let first = match waveforms.last() {
+                None => 0,
+                // the number of samples can be updated as a waveform grows
+                Some((_, previous))
+                    if (
+                        previous.start_time,
+                        previous.increment,
+                        previous.start_offset,
+                    ) == (
+                        waveform.start_time,
+                        waveform.increment,
+                        waveform.start_offset,
+                    ) =>
+                {
+                    continue
+                }
+                Some(_) => chunks
+                    .iter()
+                    .find(|c| c.segment_index >= index)
+                    .map_or(length, |c| c.samples.start),
+            };
+
+            if waveforms.last().is_some_and(|(f, _)| *f == first) {
+                waveforms.pop();
+            }
+            waveforms.push((first, waveform));
+        }
+
+        if waveforms.is_empty() {
+            return Err(General(String::from(
+                "channel is missing its waveform properties",
+            )));
+        }
+
+        let mut iter = self.channel_data::<T>(channel)?;
+        let mut values = vec![];
+
+        for (i, (first, waveform)) in waveforms.iter().enumerate() {
+            let next = waveforms.get(i + 1).map_or(length, |(f, _)| *f);
+            let samples = waveform.samples_between(start, end, next - first);
+            if samples.is_empty() {
+                continue;
+            }
 
-        return ChannelDataIter::new(vec, channel, reader);
+            let range = iter.read_range(
+                first + samples.start,
+                (samples.end - samples.start) as usize,
+            )?;
+            for (n, value) in samples.zip(range) {
+                values.push((waveform.time_of(n), value));
+            }
+        }
+
+        Ok(values)
     }
 
-    pub fn channel_data_single_float_unit(
+    /// `digital_line_data` returns an iterator over the states of a DAQmx digital line channel, one
+    /// of the lines of a digital port
+    pub fn digital_line_data(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<f32, File>, TdmsError> {
+    ) -> Result<DigitalLineIter<'_, SharedReader<File>>, TdmsError> {
+        let bit = self.digital_line_bit(channel)?;
+
+        Ok(DigitalLineIter::new(self.channel_data::<u8>(channel)?, bit))
+    }
+
+    /// digital_line_bit returns the position of a digital line channel's line within the bytes it's
+    /// read from
+    pub(crate) fn digital_line_bit(&self, channel: &Channel) -> Result<u8, TdmsError> {
         let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
 
-        return ChannelDataIter::new(vec, channel, reader);
+        match digital_line_scaler(&vec, channel) {
+            None => Err(General(format!(
+                "channel {} is not a digital line channel",
+                channel.path
+            ))),
+            Some(scaler) => Ok(scaler.bit()),
+        }
     }
 
-    pub fn channel_data_i8(
+    /// `digital_port_data` returns an iterator packing the states of several DAQmx digital line
+    /// channels into a single value per sample, the first channel's line being the least
+    /// significant bit. Up to 64 lines can be packed
+    pub fn digital_port_data(
+        &self,
+        channels: &[&'a Channel],
+    ) -> Result<DigitalPortIter<'_, SharedReader<File>>, TdmsError> {
+        if channels.len() > 64 {
+            return Err(General(format!(
+                "can't pack {} digital lines into a port value",
+                channels.len()
+            )));
+        }
+
+        let lines = channels
+            .iter()
+            .map(|c| self.digital_line_data(c))
+            .collect::<Result<Vec<_>, TdmsError>>()?;
+
+        Ok(DigitalPortIter::new(lines))
+    }
+
+    /// shorthand for `channel_data::<TdmsComplex<f64>>`
+    pub fn channel_data_complex_double_float(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<i8, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
+    ) -> Result<ChannelDataIter<TdmsComplex<f64>, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
+    }
 
-        return ChannelDataIter::new(vec, channel, reader);
+    /// shorthand for `channel_data::<TdmsComplex<f32>>`
+    pub fn channel_data_complex_single_float(
+        &self,
+        channel: &'a Channel,
+    ) -> Result<ChannelDataIter<TdmsComplex<f32>, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
-    pub fn channel_data_i16(
+    /// shorthand for `channel_data::<f64>`
+    pub fn channel_data_double_float_unit(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<i16, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
+    ) -> Result<ChannelDataIter<f64, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
+    }
 
-        return ChannelDataIter::new(vec, channel, reader);
+    /// shorthand for `channel_data::<f32>`
+    pub fn channel_data_single_float_unit(
+        &self,
+        channel: &'a Channel,
+    ) -> Result<ChannelDataIter<f32, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
-    pub fn channel_data_i32(
+    /// shorthand for `channel_data::<i8>`
+    pub fn channel_data_i8(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<i32, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
+    ) -> Result<ChannelDataIter<i8, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
+    }
 
-        return ChannelDataIter::new(vec, channel, reader);
+    /// shorthand for `channel_data::<i16>`
+    pub fn channel_data_i16(
+        &self,
+        channel: &'a Channel,
+    ) -> Result<ChannelDataIter<i16, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
-    pub fn channel_data_i64(
+    /// shorthand for `channel_data::<i32>`
+    pub fn channel_data_i32(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<i64, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
+    ) -> Result<ChannelDataIter<i32, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
+    }
 
-        return ChannelDataIter::new(vec, channel, reader);
+    /// shorthand for `channel_data::<i64>`
+    pub fn channel_data_i64(
+        &self,
+        channel: &'a Channel,
+    ) -> Result<ChannelDataIter<i64, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
+    /// shorthand for `channel_data::<u8>`
     pub fn channel_data_u8(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<u8, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
-
-        return ChannelDataIter::new(vec, channel, reader);
+    ) -> Result<ChannelDataIter<u8, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
+    /// shorthand for `channel_data::<u16>`
     pub fn channel_data_u16(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<u16, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
-
-        return ChannelDataIter::new(vec, channel, reader);
+    ) -> Result<ChannelDataIter<u16, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
+    /// shorthand for `channel_data::<u32>`
     pub fn channel_data_u32(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<u32, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
-
-        return ChannelDataIter::new(vec, channel, reader);
+    ) -> Result<ChannelDataIter<u32, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
+    /// shorthand for `channel_data::<u64>`
     pub fn channel_data_u64(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<u64, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
-
-        return ChannelDataIter::new(vec, channel, reader);
+    ) -> Result<ChannelDataIter<u64, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
+    /// shorthand for `channel_data::<bool>`
     pub fn channel_data_bool(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<bool, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
-
-        return ChannelDataIter::new(vec, channel, reader);
+    ) -> Result<ChannelDataIter<bool, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
+    /// shorthand for `channel_data::<TdmsTimestamp>`
     pub fn channel_data_timestamp(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<TdmsTimestamp, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
-
-        return ChannelDataIter::new(vec, channel, reader);
+    ) -> Result<ChannelDataIter<TdmsTimestamp, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
+    /// shorthand for `channel_data::<String>`
     pub fn channel_data_string(
         &self,
         channel: &'a Channel,
-    ) -> Result<ChannelDataIter<String, File>, TdmsError> {
-        let vec = self.load_segments(channel.group_path.as_str(), channel.path.as_str());
-        let reader = BufReader::with_capacity(4096, File::open(self.path)?);
-
-        return ChannelDataIter::new(vec, channel, reader);
+    ) -> Result<ChannelDataIter<String, SharedReader<File>>, TdmsError> {
+        self.channel_data(channel)
     }
 
     fn load_segments(&self, group_path: &str, path: &str) -> Vec<&Segment> {
         let mut vec: Vec<&Segment> = vec![];
-        let mut channel_in_segment: bool = false;
 
+        // segments carry over the object list of the segment before them unless they declare a new
+        // one, so a channel with raw data in a segment is always present in its groups
         for segment in &self.segments {
-            match segment.groups.get(group_path) {
-                None => {
-                    if !segment.has_new_obj_list() && channel_in_segment {
-                        vec.push(&segment)
-                    } else {
-                        channel_in_segment = false
+            match segment.get_channel(group_path, path) {
+                None => continue,
+                Some(channel) => {
+                    // a channel can be listed in a segment only to update its properties
+                    if !channel.chunk_positions.is_empty() {
+                        vec.push(segment);
                     }
                 }
-                Some(channels) => match channels {
-                    None => {
-                        if !segment.has_new_obj_list() && channel_in_segment {
-                            vec.push(&segment)
-                        } else {
-                            channel_in_segment = false
-                        }
-                    }
-                    Some(channels) => {
-                        let channel = channels.get(path);
-
-                        match channel {
-                            None => {
-                                if !segment.has_new_obj_list() && channel_in_segment {
-                                    vec.push(&segment)
-                                } else {
-                                    channel_in_segment = false
-                                }
-                            }
-                            Some(_) => {
-                                vec.push(&segment);
-                                channel_in_segment = true;
-                            }
-                        }
-                    }
-                },
             }
         }
 
         return vec;
     }
 }
+
+/// property_values turns merged properties into the name to value map returned for file, group and
+/// channel properties
+fn property_values<'b>(
+    properties: IndexMap<&str, &'b MetadataProperty>,
+) -> IndexMap<String, &'b TDMSValue> {
+    properties
+        .into_iter()
+        .map(|(name, property)| (name.to_string(), &property.value))
+        .collect()
+}
+
+/// raw_data_type returns the data type a channel's raw data is stored as, taken from the first of
+/// the segments holding its data. A channel's data type is only known in segments where it has raw
+/// data, it can be listed again later only to update its properties
+fn raw_data_type(segments: &[&Segment], channel: &Channel) -> TdmsDataType {
+    match segments
+        .first()
+        .and_then(|s| s.get_channel(channel.group_path.as_str(), channel.path.as_str()))
+    {
+        Some(c) => c.raw_data_type(),
+        None => channel.raw_data_type(),
+    }
+}
+
+/// digital_line_scaler returns the scaler locating a DAQmx digital line channel's line, taken from
+/// the first of the segments holding its data
+fn digital_line_scaler<'s>(
+    segments: &[&'s Segment],
+    channel: &Channel,
+) -> Option<&'s DigitalLineScaler> {
+    segments
+        .first()?
+        .get_channel(channel.group_path.as_str(), channel.path.as_str())?
+        .daqmx_data_index
+        .as_ref()?
+        .digital_line_vec
+        .as_ref()?
+        .first()
+}
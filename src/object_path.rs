@@ -0,0 +1,107 @@
+use crate::{General, TdmsError};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// `ObjectPath` is the path of an object in a TDMS file - the file's root object, a group or a
+/// channel. In a file, paths are written as `/`, `/'group'` and `/'group'/'channel'`, with any
+/// single quote inside a name escaped by doubling it. Names can contain any other character,
+/// including `/`.
+pub enum ObjectPath {
+    Root,
+    Group(String),
+    Channel(String, String),
+}
+
+impl ObjectPath {
+    /// `group` builds the path of a group from its name
+    pub fn group(name: &str) -> Self {
+        ObjectPath::Group(name.to_string())
+    }
+
+    /// `channel` builds the path of a channel from its group's and its own name
+    pub fn channel(group: &str, name: &str) -> Self {
+        ObjectPath::Channel(group.to_string(), name.to_string())
+    }
+
+    /// `parse` reads a path as it's written in a file
+    pub fn parse(path: &str) -> Result<Self, TdmsError> {
+        let invalid = || General(format!("invalid object path {}", path));
+
+        if path == "/" {
+            return Ok(ObjectPath::Root);
+        }
+
+        let mut names: Vec<String> = vec![];
+        let mut chars = path.chars().peekable();
+
+        while chars.peek().is_some() {
+            if chars.next() != Some('/') || chars.next() != Some('\'') {
+                return Err(invalid());
+            }
+
+            let mut name = String::new();
+            loop {
+                match chars.next() {
+                    None => return Err(invalid()),
+                    // a doubled quote is an escaped quote, a single one ends the name
+                    Some('\'') if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                        name.push('\'');
+                    }
+                    Some('\'') => break,
+                    Some(c) => name.push(c),
+                }
+            }
+
+            names.push(name);
+        }
+
+        let mut names = names.into_iter();
+        match (names.next(), names.next(), names.next()) {
+            (Some(group), None, None) => Ok(ObjectPath::Group(group)),
+            (Some(group), Some(channel), None) => Ok(ObjectPath::Channel(group, channel)),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// `group_name` returns the name of the group, or of the channel's group, the path refers to
+    pub fn group_name(&self) -> Option<&str> {
+        match self {
+            ObjectPath::Root => None,
+            ObjectPath::Group(group) | ObjectPath::Channel(group, _) => Some(group),
+        }
+    }
+
+    /// `channel_name` returns the name of the channel the path refers to
+    pub fn channel_name(&self) -> Option<&str> {
+        match self {
+            ObjectPath::Channel(_, channel) => Some(channel),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ObjectPath {
+    type Err = TdmsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ObjectPath::parse(s)
+    }
+}
+
+impl fmt::Display for ObjectPath {
+    /// formats the path as it's written in a file
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjectPath::Root => f.write_str("/"),
+            ObjectPath::Group(group) => write!(f, "/'{}'", group.replace('\'', "''")),
+            ObjectPath::Channel(group, channel) => write!(
+                f,
+                "/'{}'/'{}'",
+                group.replace('\'', "''"),
+                channel.replace('\'', "''")
+            ),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
+
+/// `SharedReader` lends a single underlying reader, typically an open file, to several channel
+/// iterators at once. Each clone keeps its own position and moves the underlying reader there
+/// before reading, so iterators don't disturb each other.
+#[derive(Debug)]
+pub struct SharedReader<R> {
+    shared: Arc<Mutex<Shared<R>>>,
+    pos: u64,
+}
+
+#[derive(Debug)]
+struct Shared<R> {
+    reader: R,
+    // position of the underlying reader if known, saving a seek when a handle carries on reading
+    // from where it stopped
+    pos: Option<u64>,
+}
+
+impl<R> SharedReader<R> {
+    pub fn new(reader: R) -> Self {
+        SharedReader {
+            shared: Arc::new(Mutex::new(Shared { reader, pos: None })),
+            pos: 0,
+        }
+    }
+}
+
+impl<R> Clone for SharedReader<R> {
+    /// returns a new handle to the same reader, starting at this handle's position
+    fn clone(&self) -> Self {
+        SharedReader {
+            shared: Arc::clone(&self.shared),
+            pos: self.pos,
+        }
+    }
+}
+
+impl<R: Read + Seek> Read for SharedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut shared = match self.shared.lock() {
+            Ok(s) => s,
+            Err(_) => return Err(io::Error::other("shared reader lock poisoned")),
+        };
+
+        if shared.pos != Some(self.pos) {
+            shared.pos = None;
+            shared.reader.seek(SeekFrom::Start(self.pos))?;
+        }
+
+        let read = shared.reader.read(buf)?;
+        self.pos += read as u64;
+        shared.pos = Some(self.pos);
+
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> Seek for SharedReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(p) => {
+                self.pos = p;
+                return Ok(p);
+            }
+            SeekFrom::Current(offset) => (self.pos, offset),
+            SeekFrom::End(offset) => {
+                let mut shared = match self.shared.lock() {
+                    Ok(s) => s,
+                    Err(_) => return Err(io::Error::other("shared reader lock poisoned")),
+                };
+
+                let end = shared.reader.seek(SeekFrom::End(0))?;
+                shared.pos = Some(end);
+                (end, offset)
+            }
+        };
+
+        self.pos = match base.checked_add_signed(offset) {
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "invalid seek to a negative or overflowing position",
+                ))
+            }
+            Some(p) => p,
+        };
+
+        Ok(self.pos)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.pos)
+    }
+}
@@ -0,0 +1,59 @@
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+/// tag that starts every segment of a TDMS file
+const SEGMENT_TAG: &[u8; 4] = b"TDSm";
+
+#[derive(Debug, Clone, PartialEq)]
+/// `SkippedRegion` describes a range of bytes, from `start_pos` up to but not including `end_pos`,
+/// that `TDMSFile::recover` was unable to read.
+pub struct SkippedRegion {
+    pub start_pos: u64,
+    pub end_pos: u64,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// `SkipReason` is why a region of a file was skipped during recovery
+pub enum SkipReason {
+    /// the region doesn't start with a readable segment - its lead in is missing or malformed, or
+    /// its metadata couldn't be parsed. Holds the error encountered
+    InvalidSegment(String),
+    /// raw data at the end of a truncated segment that doesn't make up a complete chunk
+    PartialChunk,
+}
+
+/// `find_segment_tag` scans forward from `pos` for the next `TDSm` tag, returning its position or
+/// `None` if there are no more tags before the end of the file
+pub(crate) fn find_segment_tag<R: Read + Seek>(
+    r: &mut R,
+    pos: u64,
+) -> Result<Option<u64>, std::io::Error> {
+    r.seek(SeekFrom::Start(pos))?;
+
+    let mut buf = [0u8; 4096];
+    // the window carries the last bytes of the previous read so tags split across reads are found
+    let mut window: Vec<u8> = Vec::with_capacity(buf.len() + SEGMENT_TAG.len());
+    let mut window_pos = pos;
+
+    loop {
+        let read = match r.read(&mut buf) {
+            Ok(0) => return Ok(None),
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        window.extend_from_slice(&buf[..read]);
+
+        if let Some(i) = window
+            .windows(SEGMENT_TAG.len())
+            .position(|w| w == SEGMENT_TAG)
+        {
+            return Ok(Some(window_pos + i as u64));
+        }
+
+        let keep = window.len().min(SEGMENT_TAG.len() - 1);
+        window_pos += (window.len() - keep) as u64;
+        window.drain(..window.len() - keep);
+    }
+}
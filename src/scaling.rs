@@ -0,0 +1,909 @@
+use crate::channel_iter::ChannelDataIter;
+use crate::data_type::{TDMSValue, TdmsDataType, TdmsNative};
+use crate::segment::MetadataProperty;
+use crate::{Endianness, General, TdmsError};
+use indexmap::IndexMap;
+use std::io;
+use std::io::{Read, Seek, Write};
+
+/// name of the property holding how many `NI_Scale[n]` scales a channel has
+pub const NUMBER_OF_SCALES_PROPERTY: &str = "NI_Number_Of_Scales";
+/// name of the property holding whether a channel's raw data has already been scaled, either
+/// `scaled` or `unscaled`
+pub const SCALING_STATUS_PROPERTY: &str = "NI_Scaling_Status";
+/// input source of a scale that takes the channel's raw data rather than the output of another scale
+pub const RAW_DATA_INPUT_SOURCE: u32 = 0xFFFFFFFF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThermocoupleType {
+    B,
+    E,
+    J,
+    K,
+    N,
+    R,
+    S,
+    T,
+}
+
+impl TryFrom<i64> for ThermocoupleType {
+    type Error = TdmsError;
+
+    /// converts the DAQmx constant stored in a scale's `Thermocouple_Type` property
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        match value {
+            10047 => Ok(ThermocoupleType::B),
+            10055 => Ok(ThermocoupleType::E),
+            10072 => Ok(ThermocoupleType::J),
+            10073 => Ok(ThermocoupleType::K),
+            10077 => Ok(ThermocoupleType::N),
+            10082 => Ok(ThermocoupleType::R),
+            10085 => Ok(ThermocoupleType::S),
+            10086 => Ok(ThermocoupleType::T),
+            _ => Err(General(format!("unknown thermocouple type {}", value))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrainConfiguration {
+    FullBridgeI,
+    FullBridgeII,
+    FullBridgeIII,
+    HalfBridgeI,
+    HalfBridgeII,
+    QuarterBridgeI,
+    QuarterBridgeII,
+}
+
+impl TryFrom<i64> for StrainConfiguration {
+    type Error = TdmsError;
+
+    /// converts the DAQmx constant stored in a scale's `Strain_Configuration` property
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        match value {
+            10183 => Ok(StrainConfiguration::FullBridgeI),
+            10184 => Ok(StrainConfiguration::FullBridgeII),
+            10185 => Ok(StrainConfiguration::FullBridgeIII),
+            10188 => Ok(StrainConfiguration::HalfBridgeI),
+            10189 => Ok(StrainConfiguration::HalfBridgeII),
+            10271 => Ok(StrainConfiguration::QuarterBridgeI),
+            10272 => Ok(StrainConfiguration::QuarterBridgeII),
+            _ => Err(General(format!("unknown strain configuration {}", value))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// `Scale` is a single conversion described by a channel's `NI_Scale[n]` properties
+pub enum Scale {
+    /// y = slope * x + y_intercept
+    Linear { slope: f64, y_intercept: f64 },
+    /// y = c0 + c1 * x + c2 * x^2 + ...
+    Polynomial { coefficients: Vec<f64> },
+    /// linear interpolation between the points of a table, sorted by their pre scaled value.
+    /// Values outside the table take the scaled value at its nearest end
+    Table {
+        pre_scaled_values: Vec<f64>,
+        scaled_values: Vec<f64>,
+    },
+    /// thermocouple voltage in volts to temperature in degrees Celsius, using the NIST ITS-90
+    /// inverse polynomials. No cold junction compensation is applied
+    Thermocouple { thermocouple_type: ThermocoupleType },
+    /// RTD voltage in volts to temperature in degrees Celsius, using the Callendar-Van Dusen equation
+    Rtd {
+        current_excitation: f64,
+        r0_nominal_resistance: f64,
+        a: f64,
+        b: f64,
+        c: f64,
+        lead_wire_resistance: f64,
+        /// 2, 3 or 4 wire
+        resistance_configuration: u32,
+    },
+    /// bridge voltage in volts to strain
+    Strain {
+        configuration: StrainConfiguration,
+        poisson_ratio: f64,
+        gage_resistance: f64,
+        lead_wire_resistance: f64,
+        initial_bridge_voltage: f64,
+        gage_factor: f64,
+        shunt_calibration_gain_adjustment: f64,
+        excitation_voltage: f64,
+    },
+    /// y = 1 / x
+    Reciprocal,
+}
+
+impl Scale {
+    /// `apply` converts a single value
+    pub fn apply(&self, x: f64) -> f64 {
+        match self {
+            Scale::Linear { slope, y_intercept } => slope * x + y_intercept,
+            Scale::Polynomial { coefficients } => polynomial(coefficients, x),
+            Scale::Table {
+                pre_scaled_values,
+                scaled_values,
+            } => interpolate(pre_scaled_values, scaled_values, x),
+            Scale::Thermocouple { thermocouple_type } => {
+                thermocouple_temperature(*thermocouple_type, x * 1000.0)
+            }
+            Scale::Rtd {
+                current_excitation,
+                r0_nominal_resistance,
+                a,
+                b,
+                c,
+                lead_wire_resistance,
+                resistance_configuration,
+            } => {
+                // 2 wire measurements include both leads, 3 wire ones cancel one of them out
+                let leads = match resistance_configuration {
+                    2 => 2.0,
+                    3 => 1.0,
+                    _ => 0.0,
+                };
+                let resistance = x / current_excitation - leads * lead_wire_resistance;
+
+                rtd_temperature(resistance / r0_nominal_resistance, *a, *b, *c)
+            }
+            Scale::Strain {
+                configuration,
+                poisson_ratio,
+                gage_resistance,
+                lead_wire_resistance,
+                initial_bridge_voltage,
+                gage_factor,
+                shunt_calibration_gain_adjustment,
+                excitation_voltage,
+            } => {
+                let vr = (x - initial_bridge_voltage) / excitation_voltage;
+                let (v, gf) = (*poisson_ratio, *gage_factor);
+                let leads = 1.0 + lead_wire_resistance / gage_resistance;
+
+                let strain = match configuration {
+                    StrainConfiguration::FullBridgeI => -vr / gf,
+                    StrainConfiguration::FullBridgeII => -2.0 * vr / (gf * (1.0 + v)),
+                    StrainConfiguration::FullBridgeIII => {
+                        -2.0 * vr / (gf * ((v + 1.0) - vr * (v - 1.0)))
+                    }
+                    StrainConfiguration::HalfBridgeI => {
+                        -4.0 * vr / (gf * ((1.0 + v) - 2.0 * vr * (v - 1.0))) * leads
+                    }
+                    StrainConfiguration::HalfBridgeII => -2.0 * vr / gf * leads,
+                    StrainConfiguration::QuarterBridgeI | StrainConfiguration::QuarterBridgeII => {
+                        -4.0 * vr / (gf * (1.0 + 2.0 * vr)) * leads
+                    }
+                };
+
+                strain * shunt_calibration_gain_adjustment
+            }
+            Scale::Reciprocal => 1.0 / x,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// `ScaleStep` is a scale along with where its input comes from - the output of the scale at
+/// `input_source` or, if that is `RAW_DATA_INPUT_SOURCE`, the channel's raw data
+pub struct ScaleStep {
+    pub scale: Scale,
+    pub input_source: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// `Scaling` converts a channel's raw values to engineering units by chaining the scales described
+/// in its properties. The last scale's output is the scaled value, each scale taking its input from
+/// an earlier one or from the raw data.
+pub struct Scaling {
+    /// indexed by scale number. Scales applied by DAQmx scalers aren't described by properties and
+    /// are `None`, they pass their input through unchanged
+    pub steps: Vec<Option<ScaleStep>>,
+}
+
+impl Scaling {
+    /// `from_properties` reads the scales from a channel's properties, properties later in the list
+    /// replacing earlier ones with the same name. Channels without scales, or whose data is marked
+    /// as already scaled, get a scaling that leaves values unchanged
+    pub fn from_properties<'b, I: IntoIterator<Item = &'b MetadataProperty>>(
+        properties: I,
+    ) -> Result<Self, TdmsError> {
+        let mut map: IndexMap<&str, &TDMSValue> = IndexMap::new();
+        for property in properties {
+            map.insert(property.name.as_str(), &property.value);
+        }
+
+        if let Some(status) = map.get(SCALING_STATUS_PROPERTY) {
+            if status.as_str()? == "scaled" {
+                return Ok(Scaling::default());
+            }
+        }
+
+        let described = map
+            .keys()
+            .filter_map(|name| scale_number(name))
+            .map(|n| n + 1)
+            .max()
+            .unwrap_or(0);
+
+        // some writers leave out the count, the highest numbered scale tells us instead
+        let count = match map.get(NUMBER_OF_SCALES_PROPERTY) {
+            Some(value) => usize::try_from(value.as_f64()? as u64)?,
+            None => described,
+        };
+
+        // the count comes straight from the file, scales beyond the highest one described can't be
+        // applied and a corrupt count mustn't decide how much is allocated
+        if count > described {
+            return Err(General(format!(
+                "channel lists {} scales but only describes {}",
+                count, described
+            )));
+        }
+
+        let mut steps = Vec::with_capacity(count);
+        for n in 0..count {
+            let scale_type = match map.get(format!("NI_Scale[{}]_Scale_Type", n).as_str()) {
+                None => {
+                    steps.push(None);
+                    continue;
+                }
+                Some(value) => value.as_str()?,
+            };
+
+            let properties = ScaleProperties {
+                map: &map,
+                prefix: format!("NI_Scale[{}]_{}_", n, scale_type),
+            };
+
+            let scale = match scale_type {
+                "Linear" => Scale::Linear {
+                    slope: properties.f64("Slope")?,
+                    y_intercept: properties.f64("Y_Intercept")?,
+                },
+                "Polynomial" => Scale::Polynomial {
+                    coefficients: properties.f64_vec("Coefficients")?,
+                },
+                "Table" => {
+                    let pre_scaled = properties.f64_vec("Pre_Scaled_Values")?;
+                    let scaled = properties.f64_vec("Scaled_Values")?;
+                    if pre_scaled.len() != scaled.len() || pre_scaled.is_empty() {
+                        return Err(General(format!(
+                            "scale {} has a table with mismatched or empty values",
+                            n
+                        )));
+                    }
+
+                    let mut points: Vec<(f64, f64)> = pre_scaled.into_iter().zip(scaled).collect();
+                    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+                    Scale::Table {
+                        pre_scaled_values: points.iter().map(|p| p.0).collect(),
+                        scaled_values: points.iter().map(|p| p.1).collect(),
+                    }
+                }
+                "Thermocouple" => Scale::Thermocouple {
+                    thermocouple_type: ThermocoupleType::try_from(properties.i64("Type")?)?,
+                },
+                "RTD" => Scale::Rtd {
+                    current_excitation: properties.f64("Current_Excitation")?,
+                    r0_nominal_resistance: properties.f64("R0_Nominal_Resistance")?,
+                    a: properties.f64("A")?,
+                    b: properties.f64("B")?,
+                    c: properties.f64("C")?,
+                    lead_wire_resistance: properties.f64("Lead_Wire_Resistance")?,
+                    resistance_configuration: u32::try_from(
+                        properties.i64("Resistance_Configuration")?,
+                    )?,
+                },
+                "Strain" => Scale::Strain {
+                    configuration: StrainConfiguration::try_from(properties.i64("Configuration")?)?,
+                    poisson_ratio: properties.f64("Poisson_Ratio")?,
+                    gage_resistance: properties.f64("Gage_Resistance")?,
+                    lead_wire_resistance: properties.f64("Lead_Wire_Resistance")?,
+                    initial_bridge_voltage: properties.f64("Initial_Bridge_Voltage")?,
+                    gage_factor: properties.f64("Gage_Factor")?,
+                    shunt_calibration_gain_adjustment: properties
+                        .f64("Bridge_Shunt_Calibration_Gain_Adjustment")?,
+                    excitation_voltage: properties.f64("Excitation_Voltage")?,
+                },
+                "Reciprocal" => Scale::Reciprocal,
+                other => {
+                    return Err(General(format!(
+                        "scale {} has unsupported type {}",
+                        n, other
+                    )))
+                }
+            };
+
+            let input_source = match properties.get("Input_Source") {
+                None => RAW_DATA_INPUT_SOURCE,
+                Some(_) => u32::try_from(properties.i64("Input_Source")?)?,
+            };
+
+            // scales can only build on earlier ones, which also rules out cycles
+            if input_source != RAW_DATA_INPUT_SOURCE && input_source as usize >= n {
+                return Err(General(format!(
+                    "scale {} has invalid input source {}",
+                    n, input_source
+                )));
+            }
+
+            steps.push(Some(ScaleStep {
+                scale,
+                input_source,
+            }));
+        }
+
+        Ok(Scaling { steps })
+    }
+
+    /// `is_identity` returns whether scaling leaves values unchanged
+    pub fn is_identity(&self) -> bool {
+        self.steps.iter().all(|s| s.is_none())
+    }
+
+    /// `scale` converts a raw value to engineering units
+    pub fn scale(&self, raw: f64) -> f64 {
+        match self.steps.len() {
+            0 => raw,
+            n => self.output_of(n - 1, raw),
+        }
+    }
+
+    fn output_of(&self, index: usize, raw: f64) -> f64 {
+        match self.steps.get(index) {
+            Some(Some(step)) => {
+                let input = match step.input_source {
+                    RAW_DATA_INPUT_SOURCE => raw,
+                    source => self.output_of(source as usize, raw),
+                };
+
+                step.scale.apply(input)
+            }
+            _ => raw,
+        }
+    }
+}
+
+/// ScaleProperties looks up the properties of a single scale by the part of their name following
+/// the scale's prefix, e.g. `Slope` for `NI_Scale[1]_Linear_Slope`
+struct ScaleProperties<'b> {
+    map: &'b IndexMap<&'b str, &'b TDMSValue>,
+    prefix: String,
+}
+
+impl<'b> ScaleProperties<'b> {
+    fn get(&self, name: &str) -> Option<&'b TDMSValue> {
+        self.map
+            .get(format!("{}{}", self.prefix, name).as_str())
+            .copied()
+    }
+
+    fn f64(&self, name: &str) -> Result<f64, TdmsError> {
+        match self.get(name) {
+            None => Err(General(format!(
+                "missing scaling property {}{}",
+                self.prefix, name
+            ))),
+            Some(value) => value.as_f64(),
+        }
+    }
+
+    fn i64(&self, name: &str) -> Result<i64, TdmsError> {
+        let value = self.f64(name)?;
+        if value.fract() != 0.0 {
+            return Err(General(format!(
+                "scaling property {}{} isn't a whole number",
+                self.prefix, name
+            )));
+        }
+
+        Ok(value as i64)
+    }
+
+    /// f64_vec reads an array stored as a `_Size` property followed by one property per element,
+    /// e.g. `Coefficients_Size` and `Coefficients[0]`
+    fn f64_vec(&self, name: &str) -> Result<Vec<f64>, TdmsError> {
+        let size = self.i64(format!("{}_Size", name).as_str())?;
+
+        (0..size)
+            .map(|i| self.f64(format!("{}[{}]", name, i).as_str()))
+            .collect()
+    }
+}
+
+/// scale_number returns `n` for the `NI_Scale[n]_Scale_Type` property
+fn scale_number(name: &str) -> Option<usize> {
+    name.strip_prefix("NI_Scale[")?
+        .strip_suffix("]_Scale_Type")?
+        .parse()
+        .ok()
+}
+
+fn polynomial(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().rev().fold(0.0, |acc, c| acc * x + c)
+}
+
+fn interpolate(xs: &[f64], ys: &[f64], x: f64) -> f64 {
+    let i = xs.partition_point(|v| *v < x);
+
+    if i == 0 {
+        return ys[0];
+    }
+    if i == xs.len() {
+        return ys[xs.len() - 1];
+    }
+
+    let (x0, x1, y0, y1) = (xs[i - 1], xs[i], ys[i - 1], ys[i]);
+    y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+}
+
+/// rtd_temperature solves the Callendar-Van Dusen equation
+/// R / R0 = 1 + A*T + B*T^2 + C*(T - 100)*T^3 for T, where C only applies below zero
+fn rtd_temperature(ratio: f64, a: f64, b: f64, c: f64) -> f64 {
+    let t = if b == 0.0 {
+        (ratio - 1.0) / a
+    } else {
+        (-a + (a * a - 4.0 * b * (1.0 - ratio)).sqrt()) / (2.0 * b)
+    };
+
+    if t >= 0.0 || c == 0.0 {
+        return t;
+    }
+
+    // below zero there's no closed form, refine the quadratic solution with Newton's method
+    let mut t = t;
+    for _ in 0..20 {
+        let f = 1.0 + a * t + b * t * t + c * (t - 100.0) * t.powi(3) - ratio;
+        let df = a + 2.0 * b * t + c * (4.0 * t.powi(3) - 300.0 * t * t);
+        let step = f / df;
+        t -= step;
+
+        if step.abs() < 1e-9 {
+            break;
+        }
+    }
+
+    t
+}
+
+/// NIST ITS-90 inverse polynomials for each thermocouple type, each range given by the highest
+/// voltage in millivolts it applies to along with its coefficients
+const THERMOCOUPLE_B: &[(f64, &[f64])] = &[
+    (
+        2.431,
+        &[
+            9.8423321E+01,
+            6.9971500E+02,
+            -8.4765304E+02,
+            1.0052644E+03,
+            -8.3345952E+02,
+            4.5508542E+02,
+            -1.5523037E+02,
+            2.9886750E+01,
+            -2.4742860E+00,
+        ],
+    ),
+    (
+        13.820,
+        &[
+            2.1315071E+02,
+            2.8510504E+02,
+            -5.2742887E+01,
+            9.9160804E+00,
+            -1.2965303E+00,
+            1.1195870E-01,
+            -6.0625199E-03,
+            1.8661696E-04,
+            -2.4878585E-06,
+        ],
+    ),
+];
+
+const THERMOCOUPLE_E: &[(f64, &[f64])] = &[
+    (
+        0.0,
+        &[
+            0.0,
+            1.6977288E+01,
+            -4.3514970E-01,
+            -1.5859697E-01,
+            -9.2502871E-02,
+            -2.6084314E-02,
+            -4.1360199E-03,
+            -3.4034030E-04,
+            -1.1564890E-05,
+        ],
+    ),
+    (
+        76.373,
+        &[
+            0.0,
+            1.7057035E+01,
+            -2.3301759E-01,
+            6.5435585E-03,
+            -7.3562749E-05,
+            -1.7896001E-06,
+            8.4036165E-08,
+            -1.3735879E-09,
+            1.0629823E-11,
+            -3.2447087E-14,
+        ],
+    ),
+];
+
+const THERMOCOUPLE_J: &[(f64, &[f64])] = &[
+    (
+        0.0,
+        &[
+            0.0,
+            1.9528268E+01,
+            -1.2286185E+00,
+            -1.0752178E+00,
+            -5.9086933E-01,
+            -1.7256713E-01,
+            -2.8131513E-02,
+            -2.3963370E-03,
+            -8.3823321E-05,
+        ],
+    ),
+    (
+        42.919,
+        &[
+            0.0,
+            1.978425E+01,
+            -2.001204E-01,
+            1.036969E-02,
+            -2.549687E-04,
+            3.585153E-06,
+            -5.344285E-08,
+            5.099890E-10,
+        ],
+    ),
+    (
+        69.553,
+        &[
+            -3.11358187E+03,
+            3.00543684E+02,
+            -9.94773230E+00,
+            1.70276630E-01,
+            -1.43033468E-03,
+            4.73886084E-06,
+        ],
+    ),
+];
+
+const THERMOCOUPLE_K: &[(f64, &[f64])] = &[
+    (
+        0.0,
+        &[
+            0.0,
+            2.5173462E+01,
+            -1.1662878E+00,
+            -1.0833638E+00,
+            -8.9773540E-01,
+            -3.7342377E-01,
+            -8.6632643E-02,
+            -1.0450598E-02,
+            -5.1920577E-04,
+        ],
+    ),
+    (
+        20.644,
+        &[
+            0.0,
+            2.508355E+01,
+            7.860106E-02,
+            -2.503131E-01,
+            8.315270E-02,
+            -1.228034E-02,
+            9.804036E-04,
+            -4.413030E-05,
+            1.057734E-06,
+            -1.052755E-08,
+        ],
+    ),
+    (
+        54.886,
+        &[
+            -1.318058E+02,
+            4.830222E+01,
+            -1.646031E+00,
+            5.464731E-02,
+            -9.650715E-04,
+            8.802193E-06,
+            -3.110810E-08,
+        ],
+    ),
+];
+
+const THERMOCOUPLE_N: &[(f64, &[f64])] = &[
+    (
+        0.0,
+        &[
+            0.0,
+            3.8436847E+01,
+            1.1010485E+00,
+            5.2229312E+00,
+            7.2060525E+00,
+            5.8488586E+00,
+            2.7754916E+00,
+            7.7075166E-01,
+            1.1582665E-01,
+            7.3138868E-03,
+        ],
+    ),
+    (
+        20.613,
+        &[
+            0.0,
+            3.86896E+01,
+            -1.08267E+00,
+            4.70205E-02,
+            -2.12169E-06,
+            -1.17272E-04,
+            5.39280E-06,
+            -7.98156E-08,
+        ],
+    ),
+    (
+        47.513,
+        &[
+            1.972485E+01,
+            3.300943E+01,
+            -3.915159E-01,
+            9.855391E-03,
+            -1.274371E-04,
+            7.767022E-07,
+        ],
+    ),
+];
+
+const THERMOCOUPLE_R: &[(f64, &[f64])] = &[
+    (
+        1.923,
+        &[
+            0.0,
+            1.8891380E+02,
+            -9.3835290E+01,
+            1.3068619E+02,
+            -2.2703580E+02,
+            3.5145659E+02,
+            -3.8953900E+02,
+            2.8239471E+02,
+            -1.2607281E+02,
+            3.1353611E+01,
+            -3.3187769E+00,
+        ],
+    ),
+    (
+        13.228,
+        &[
+            1.334584505E+01,
+            1.472644573E+02,
+            -1.844024844E+01,
+            4.031129726E+00,
+            -6.249428360E-01,
+            6.468412046E-02,
+            -4.458750426E-03,
+            1.994710149E-04,
+            -5.313401790E-06,
+            6.481976217E-08,
+        ],
+    ),
+    (
+        19.739,
+        &[
+            -8.199599416E+01,
+            1.553962042E+02,
+            -8.342197663E+00,
+            4.279433549E-01,
+            -1.191577910E-02,
+            1.492290091E-04,
+        ],
+    ),
+    (
+        21.103,
+        &[
+            3.406177836E+04,
+            -7.023729171E+03,
+            5.582903813E+02,
+            -1.952394635E+01,
+            2.560740231E-01,
+        ],
+    ),
+];
+
+const THERMOCOUPLE_S: &[(f64, &[f64])] = &[
+    (
+        1.874,
+        &[
+            0.0,
+            1.84949460E+02,
+            -8.00504062E+01,
+            1.02237430E+02,
+            -1.52248592E+02,
+            1.88821343E+02,
+            -1.59085941E+02,
+            8.23027880E+01,
+            -2.34181944E+01,
+            2.79786260E+00,
+        ],
+    ),
+    (
+        11.950,
+        &[
+            1.291507177E+01,
+            1.466298863E+02,
+            -1.534713402E+01,
+            3.145945973E+00,
+            -4.163257839E-01,
+            3.187963771E-02,
+            -1.291637500E-03,
+            2.183475087E-05,
+            -1.447379511E-07,
+            8.211272125E-09,
+        ],
+    ),
+    (
+        17.536,
+        &[
+            -8.087801117E+01,
+            1.621573104E+02,
+            -8.536869453E+00,
+            4.719686976E-01,
+            -1.441693666E-02,
+            2.081618890E-04,
+        ],
+    ),
+    (
+        18.693,
+        &[
+            5.333875126E+04,
+            -1.235892298E+04,
+            1.092657613E+03,
+            -4.265693686E+01,
+            6.247205420E-01,
+        ],
+    ),
+];
+
+const THERMOCOUPLE_T: &[(f64, &[f64])] = &[
+    (
+        0.0,
+        &[
+            0.0,
+            2.5949192E+01,
+            -2.1316967E-01,
+            7.9018692E-01,
+            4.2527777E-01,
+            1.3304473E-01,
+            2.0241446E-02,
+            1.2668171E-03,
+        ],
+    ),
+    (
+        20.872,
+        &[
+            0.0,
+            2.592800E+01,
+            -7.602961E-01,
+            4.637791E-02,
+            -2.165394E-03,
+            6.048144E-05,
+            -7.293422E-07,
+        ],
+    ),
+];
+
+/// thermocouple_temperature converts a thermocouple voltage in millivolts to degrees Celsius.
+/// Voltages outside the type's range use the polynomial of the nearest range
+fn thermocouple_temperature(thermocouple_type: ThermocoupleType, millivolts: f64) -> f64 {
+    let ranges = match thermocouple_type {
+        ThermocoupleType::B => THERMOCOUPLE_B,
+        ThermocoupleType::E => THERMOCOUPLE_E,
+        ThermocoupleType::J => THERMOCOUPLE_J,
+        ThermocoupleType::K => THERMOCOUPLE_K,
+        ThermocoupleType::N => THERMOCOUPLE_N,
+        ThermocoupleType::R => THERMOCOUPLE_R,
+        ThermocoupleType::S => THERMOCOUPLE_S,
+        ThermocoupleType::T => THERMOCOUPLE_T,
+    };
+
+    let coefficients = ranges
+        .iter()
+        .find(|(max, _)| millivolts <= *max)
+        .unwrap_or(&ranges[ranges.len() - 1])
+        .1;
+
+    polynomial(coefficients, millivolts)
+}
+
+/// RawValue is a raw integer or floating point value converted to `f64`, the input to scaling
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RawValue(pub(crate) f64);
+
+impl TdmsNative for RawValue {
+    const DATA_TYPE: TdmsDataType = TdmsDataType::DoubleFloat(8);
+
+    fn write_to<W: Write>(&self, w: &mut W, endianness: Endianness) -> io::Result<()> {
+        self.0.write_to(w, endianness)
+    }
+
+    fn read_from(bytes: &[u8], endianness: Endianness) -> Result<Self, TdmsError> {
+        Ok(RawValue(f64::read_from(bytes, endianness)?))
+    }
+
+    fn reads_data_type(data_type: TdmsDataType) -> bool {
+        matches!(
+            data_type,
+            TdmsDataType::I8(_)
+                | TdmsDataType::I16(_)
+                | TdmsDataType::I32(_)
+                | TdmsDataType::I64(_)
+                | TdmsDataType::U8(_)
+                | TdmsDataType::U16(_)
+                | TdmsDataType::U32(_)
+                | TdmsDataType::U64(_)
+                | TdmsDataType::SingleFloat(_)
+                | TdmsDataType::SingleFloatWithUnit(_)
+        ) || f64::reads_data_type(data_type)
+    }
+
+    fn read_from_data_type(
+        bytes: &[u8],
+        data_type: TdmsDataType,
+        endianness: Endianness,
+    ) -> Result<Self, TdmsError> {
+        let value = match data_type {
+            TdmsDataType::I8(_) => i8::read_from(bytes, endianness)? as f64,
+            TdmsDataType::I16(_) => i16::read_from(bytes, endianness)? as f64,
+            TdmsDataType::I32(_) => i32::read_from(bytes, endianness)? as f64,
+            TdmsDataType::I64(_) => i64::read_from(bytes, endianness)? as f64,
+            TdmsDataType::U8(_) => u8::read_from(bytes, endianness)? as f64,
+            TdmsDataType::U16(_) => u16::read_from(bytes, endianness)? as f64,
+            TdmsDataType::U32(_) => u32::read_from(bytes, endianness)? as f64,
+            TdmsDataType::U64(_) => u64::read_from(bytes, endianness)? as f64,
+            TdmsDataType::SingleFloat(_) | TdmsDataType::SingleFloatWithUnit(_) => {
+                f32::read_from(bytes, endianness)? as f64
+            }
+            data_type => f64::read_from_data_type(bytes, data_type, endianness)?,
+        };
+
+        Ok(RawValue(value))
+    }
+}
+
+/// `ScaledIter` iterates through a channel's values in engineering units, reading its raw integer
+/// or floating point values and applying the channel's scaling to each.
+pub struct ScaledIter<'a, R: Read + Seek> {
+    raw: ChannelDataIter<'a, RawValue, R>,
+    scaling: Scaling,
+}
+
+impl<'a, R: Read + Seek> ScaledIter<'a, R> {
+    pub(crate) fn new(raw: ChannelDataIter<'a, RawValue, R>, scaling: Scaling) -> Self {
+        ScaledIter { raw, scaling }
+    }
+
+    pub fn scaling(&self) -> &Scaling {
+        &self.scaling
+    }
+
+    /// `next_with_raw` returns the next value as a (raw, scaled) pair
+    pub fn next_with_raw(&mut self) -> Option<(f64, f64)> {
+        let raw = self.raw.next()?.0;
+
+        Some((raw, self.scaling.scale(raw)))
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for ScaledIter<'a, R> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_with_raw()?.1)
+    }
+}
@@ -1,23 +1,29 @@
-use crate::data_type::{TDMSValue, TdmsDataType};
+use crate::data_type::{read_bytes, TDMSValue, TdmsDataType};
+use crate::object_path::ObjectPath;
 use crate::{to_i32, to_u32, to_u64};
 use crate::{
     Big, General, InvalidDAQmxDataIndex, InvalidSegment, Little, StringConversionError, TdmsError,
+    UnknownDataType,
 };
 use indexmap::{indexmap, IndexMap};
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
 
 /// These are bitmasks for the Table of Contents byte.
-const K_TOC_META_DATA: u32 = 1 << 1;
+pub(crate) const K_TOC_META_DATA: u32 = 1 << 1;
 // this flag represents a segment who's channel list/order has been changed from the previous segments
 // and therefore a new order for processing the raw data must be followed
-const K_TOC_NEW_OBJ_LIST: u32 = 1 << 2;
-const K_TOC_RAW_DATA: u32 = 1 << 3;
-const K_TOC_INTERLEAVED_DATA: u32 = 1 << 5;
-const K_TOC_BIG_ENDIAN: u32 = 1 << 6;
-const K_TOC_DAQMX_RAW_DATA: u32 = 1 << 7;
+pub(crate) const K_TOC_NEW_OBJ_LIST: u32 = 1 << 2;
+pub(crate) const K_TOC_RAW_DATA: u32 = 1 << 3;
+pub(crate) const K_TOC_INTERLEAVED_DATA: u32 = 1 << 5;
+pub(crate) const K_TOC_BIG_ENDIAN: u32 = 1 << 6;
+pub(crate) const K_TOC_DAQMX_RAW_DATA: u32 = 1 << 7;
+
+/// next segment offset written to the lead in of a segment that is still being written
+pub(crate) const INCOMPLETE_SEGMENT_OFFSET: u64 = 0xFFFFFFFFFFFFFFFF;
 
 /// Ease of use enum for determining how to read numerical values.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Endianness {
     Little,
     Big,
@@ -31,6 +37,9 @@ pub struct Segment {
     pub start_pos: u64,
     pub end_pos: u64,
     pub groups: IndexMap<GroupPath, Option<IndexMap<ChannelPath, Channel>>>,
+    /// the segment's channels in object list order, which is the order their raw data is laid out
+    /// in. Channels can be listed in any order regardless of their group
+    pub channel_order: Vec<(GroupPath, ChannelPath)>,
     pub chunk_size: u64,
 }
 
@@ -56,6 +65,66 @@ pub struct Channel {
 #[derive(Clone, Debug, Copy)]
 pub struct ChannelPositions(pub u64, pub u64);
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// `ChannelChunk` describes one chunk of a channel's raw data, as listed by
+/// `TDMSFile::channel_chunks`.
+pub struct ChannelChunk {
+    /// index of the chunk's segment in `TDMSFile.segments`
+    pub segment_index: usize,
+    /// position in the file of the chunk's first value
+    pub offset: u64,
+    /// indices of the chunk's values, counting from the channel's first value in the file
+    pub samples: Range<u64>,
+    /// bytes from the start of one value to the next, zero for strings whose values vary in size
+    pub stride: u64,
+}
+
+impl Channel {
+    /// `raw_data_type` returns the data type of the channel's values as they are stored in raw data.
+    /// This is the channel's data type except for DAQmx channels, whose raw values are described by
+    /// their first scaler - digital line channels are read a byte at a time, the byte holding their
+    /// line
+    pub fn raw_data_type(&self) -> TdmsDataType {
+        let location = self
+            .daqmx_data_index
+            .as_ref()
+            .and_then(|i| i.raw_location());
+
+        match location {
+            Some((_, _, data_type)) => data_type,
+            None => self.data_type,
+        }
+    }
+
+    /// `stride` returns the number of bytes from the start of one of the channel's values to the
+    /// next within a chunk, zero for strings whose values vary in size
+    pub fn stride(&self) -> u64 {
+        match self.raw_data_type() {
+            TdmsDataType::String => 0,
+            data_type => TdmsDataType::get_size(data_type) as u64 + self.interleaved_offset,
+        }
+    }
+
+    /// `values_in_chunk` returns the number of the channel's values held in the chunk at `chunk` in
+    /// `chunk_positions`, which can be fewer than the raw data index lists for a chunk that was cut
+    /// short
+    pub fn values_in_chunk(&self, chunk: usize) -> u64 {
+        let ChannelPositions(start, end) = match self.chunk_positions.get(chunk) {
+            None => return 0,
+            Some(positions) => *positions,
+        };
+
+        match self.stride() {
+            0 => match &self.raw_data_index {
+                Some(index) if self.data_type == TdmsDataType::String => index.number_of_values,
+                _ => 0,
+            },
+            // the last value of a chunk isn't followed by the other channels' values
+            stride => (end - start + self.interleaved_offset) / stride,
+        }
+    }
+}
+
 impl Segment {
     /// `new` expects a reader who's cursor position is at the start of a new TDMS segment.
     /// You will see an InvalidSegment error return if the reader position isn't correct as the first
@@ -72,8 +141,62 @@ impl Segment {
 
         let lead_in = LeadIn::from_bytes(&lead_in)?;
 
-        // calculate the end position by taking the start and adding the offset plus lead in bytes
-        let segment_end_pos = lead_in.next_segment_offset + 28 + segment_start_pos;
+        // the stream's length is needed for segments that are still being written
+        let stream_len = r.seek(SeekFrom::End(0))?;
+        r.seek(SeekFrom::Start(segment_start_pos + 28))?;
+
+        Segment::from_lead_in(r, lead_in, segment_start_pos, stream_len, previous_segment)
+    }
+
+    /// `from_index` reads a segment from a `.tdms_index` file. Index segments hold only the lead in
+    /// and metadata of the segment they describe, so the caller must provide the position the
+    /// segment starts at in the TDMS file itself, and the file's length - all positions are
+    /// calculated relative to the file.
+    pub fn from_index<R: Read + Seek>(
+        r: &mut R,
+        previous_segment: Option<&Segment>,
+        segment_start_pos: u64,
+        file_len: u64,
+    ) -> Result<Self, TdmsError> {
+        let mut lead_in = [0; 28];
+
+        r.read_exact(&mut lead_in[..])?;
+
+        let lead_in = LeadIn::from_index_bytes(&lead_in)?;
+
+        Segment::from_lead_in(r, lead_in, segment_start_pos, file_len, previous_segment)
+    }
+
+    fn from_lead_in<R: Read + Seek>(
+        r: &mut R,
+        lead_in: LeadIn,
+        segment_start_pos: u64,
+        file_len: u64,
+        previous_segment: Option<&Segment>,
+    ) -> Result<Self, TdmsError> {
+        // calculate the end position by taking the start and adding the offset plus lead in bytes.
+        // Segments still being written have an offset of 0xFFFFFFFFFFFFFFFF and a writer can stop
+        // before writing every byte its lead in promised, in both cases the segment currently ends
+        // where the file does
+        let segment_end_pos = match lead_in
+            .next_segment_offset
+            .checked_add(28 + segment_start_pos)
+        {
+            Some(end_pos)
+                if lead_in.next_segment_offset != INCOMPLETE_SEGMENT_OFFSET
+                    && end_pos <= file_len =>
+            {
+                end_pos
+            }
+            _ => file_len,
+        };
+
+        // every other position in the segment is relative to the start of its raw data, once this
+        // is known not to overflow neither will they
+        let raw_data_start = match segment_start_pos.checked_add(lead_in.raw_data_offset) {
+            None => return Err(InvalidSegment()),
+            Some(pos) => pos,
+        };
 
         let endianness = if lead_in.table_of_contents & K_TOC_BIG_ENDIAN != 0 {
             Big
@@ -83,226 +206,123 @@ impl Segment {
 
         let mut metadata: Option<Metadata> = None;
         if lead_in.table_of_contents & K_TOC_META_DATA != 0 {
-            metadata = Some(Metadata::from_reader(endianness, r)?);
+            // metadata ends where the raw data starts, holding the reader to that stops a corrupt
+            // length from reading on into the raw data
+            let mut r = r.by_ref().take(lead_in.raw_data_offset - 28);
+            metadata = Some(Metadata::from_reader(endianness, &mut r)?);
         }
 
         // if we have have metadata, load up group and channel list for the segment - I debated
         // somehow building this list dynamically as we read the file but honestly the performance
         // hit according to benches was minimal and this makes a cleaner set of function boundaries
-        // and lets us get away from passing in mutable state all over the place
+        // and lets us get away from passing in mutable state all over the place. Segments without a
+        // new object list keep the previous segment's objects in the same order, only new or changed
+        // objects are listed in their metadata
         let mut groups: IndexMap<GroupPath, Option<IndexMap<ChannelPath, Channel>>> =
-            IndexMap::<GroupPath, Option<IndexMap<ChannelPath, Channel>>>::new();
-
-        // this variable will tell us where we're at in the raw data of the channel, allowing us to
-        // set start and end thresholds of the channel's data itself
-        let mut data_pos: u64 = segment_start_pos + lead_in.raw_data_offset;
-        let mut interleaved_total_size: u64 = 0;
-        let mut chunk_size: u64 = 0;
-
-        match &mut metadata {
-            Some(metadata) => {
-                for obj in &mut metadata.objects {
-                    let path = obj.object_path.clone();
-                    let paths: Vec<&str> = path.split("/").collect();
-
-                    if obj.object_path == "/" || paths.len() < 3 {
-                        continue;
-                    }
-                    let mut data_type: TdmsDataType = TdmsDataType::Void;
-
-                    if previous_segment.is_some()
-                        && obj.raw_data_index.is_none()
-                        && obj.daqmx_data_index.is_none()
-                    {
-                        match previous_segment
-                            .unwrap()
-                            .get_channel(rem_quotes(paths[1]), rem_quotes(paths[2]))
-                        {
-                            None => {}
-                            Some(c) => {
-                                obj.raw_data_index = match &c.raw_data_index {
-                                    None => None,
-                                    Some(r) => Some(r.clone()),
-                                };
-
-                                obj.daqmx_data_index = match &c.daqmx_data_index {
-                                    None => None,
-                                    Some(d) => Some(d.clone()),
-                                }
-                            }
-                        }
-                    }
-
-                    match &obj.raw_data_index {
-                        None => {}
-                        Some(index) => data_type = index.data_type,
-                    }
+            match previous_segment {
+                Some(previous) if lead_in.table_of_contents & K_TOC_NEW_OBJ_LIST == 0 => {
+                    previous.groups.clone()
+                }
+                _ => IndexMap::<GroupPath, Option<IndexMap<ChannelPath, Channel>>>::new(),
+            };
+        // objects new to an inherited list are appended to it
+        let mut channel_order: Vec<(GroupPath, ChannelPath)> = match previous_segment {
+            Some(previous) if lead_in.table_of_contents & K_TOC_NEW_OBJ_LIST == 0 => {
+                previous.channel_order.clone()
+            }
+            _ => vec![],
+        };
 
-                    match &obj.daqmx_data_index {
-                        None => {}
-                        Some(index) => data_type = index.data_type,
-                    }
+        if let Some(metadata) = &mut metadata {
+            for obj in &mut metadata.objects {
+                // the root object has no group or channel to register
+                let (group_path, channel_path) = match ObjectPath::parse(&obj.object_path)? {
+                    ObjectPath::Root => continue,
+                    ObjectPath::Group(group) => (group, None),
+                    ObjectPath::Channel(group, channel) => (group, Some(channel)),
+                };
+                let group_path = group_path.as_str();
 
-                    // add to the total interleaved size so we can calculate the offset later if needed
-                    interleaved_total_size += TdmsDataType::get_size(data_type) as u64;
+                if !groups.contains_key(group_path) {
+                    let _ = groups.insert(group_path.to_string(), None);
+                }
 
-                    if paths.len() >= 2 && paths[1] != "" {
-                        if !groups.contains_key(rem_quotes(paths[1])) {
-                            let _ = groups.insert(rem_quotes(paths[1]).to_string(), None);
-                        }
-                    }
+                let channel_path = match &channel_path {
+                    None => continue,
+                    Some(channel) => channel.as_str(),
+                };
 
-                    if paths.len() >= 3 && paths[2] != "" {
-                        let map = groups.get_mut(rem_quotes(paths[1]));
-                        let mut start_pos = data_pos.clone();
-                        let mut end_pos = 0;
-
-                        let mut string_offset_pos: Option<ChannelPositions> = None;
-                        let raw_data_index = match &obj.raw_data_index {
-                            Some(index) => {
-                                let type_size = TdmsDataType::get_size(index.data_type);
-
-                                // if not interleaved, the end threshold comes at chunk end
-                                if lead_in.table_of_contents & K_TOC_INTERLEAVED_DATA == 0 {
-                                    if index.data_type == TdmsDataType::String
-                                        && index.number_of_bytes.is_some()
-                                    {
-                                        start_pos = start_pos + index.number_of_values * 4;
-                                        string_offset_pos = Some(ChannelPositions(data_pos, data_pos + index.number_of_values * 4));
-
-                                        data_pos = data_pos + index.number_of_bytes.unwrap();
-                                        end_pos = data_pos.clone();
-                                        chunk_size += index.number_of_bytes.unwrap();
-                                    } else {
-                                        data_pos = data_pos
-                                            + (type_size as u64
-                                                * index.array_dimension as u64
-                                                * index.number_of_values);
-
-                                        end_pos = data_pos.clone();
-                                        chunk_size += type_size as u64
-                                            * index.array_dimension as u64
-                                            * index.number_of_values
-                                    }
-                                }
-
-                                Some(index.clone())
-                            }
+                // the index is the one this channel used last, it is either still in our
+                // inherited object list or in the previous segment's
+                if obj.raw_data_index_matches_previous {
+                    let previous_channel = match get_channel(&groups, group_path, channel_path) {
+                        Some(c) => Some(c),
+                        None => match previous_segment {
                             None => None,
-                        };
+                            Some(previous) => previous.get_channel(group_path, channel_path),
+                        },
+                    };
 
-                        let daqmx_data_index = match &obj.daqmx_data_index {
-                            Some(index) => Some(index.clone()),
-                            None => None,
-                        };
-
-                        let channel = Channel {
-                            full_path: obj.object_path.clone(),
-                            group_path: rem_quotes(paths[1]).to_string(),
-                            path: rem_quotes(paths[2]).to_string(),
-                            data_type,
-                            raw_data_index,
-                            daqmx_data_index,
-                            properties: obj.properties.clone(),
-                            chunk_positions: vec![ChannelPositions(start_pos, end_pos)],
-                            // this will be calculated later as we need all the channels information
-                            // prior to calculating this offset
-                            interleaved_offset: 0,
-                            string_offset_pos
-                        };
-
-                        match map {
-                            Some(map) => {
-                                match map {
-                                    Some(map) => {
-                                        map.insert(rem_quotes(paths[2]).to_string(), channel);
-                                    }
-                                    None => {
-                                        groups.insert(
-                                    rem_quotes(paths[1]).to_string(),
-                                    Some(indexmap! {rem_quotes(paths[2]).to_string() => channel}),
-                                );
-                                    }
-                                }
-                            }
-                            None => (),
+                    match previous_channel {
+                        None => {}
+                        Some(c) => {
+                            obj.raw_data_index = c.raw_data_index.clone();
+                            obj.daqmx_data_index = c.daqmx_data_index.clone();
                         }
                     }
                 }
-            }
-            _ => (),
-        }
 
-        if lead_in.table_of_contents & K_TOC_INTERLEAVED_DATA != 0 {
-            for (_, channels) in groups.iter_mut() {
-                match channels {
-                    None => continue,
-                    Some(channels) => {
-                        for (_, channel) in channels.iter_mut() {
-                            let size = TdmsDataType::get_size(channel.data_type);
-
-                            // offset tells the iterator how many bytes to move to the next value
-                            channel.interleaved_offset = interleaved_total_size - size as u64;
-                            // update the end_pos_threshold  now that we have an idea of setup
-                            match channel.chunk_positions.get_mut(0) {
-                                None => (),
-                                Some(positions) => {
-                                    positions.1 = chunk_size - channel.interleaved_offset
-                                        + interleaved_total_size
-                                }
-                            }
-
-                            interleaved_total_size += size as u64;
-                        }
-                    }
+                let mut data_type: TdmsDataType = TdmsDataType::Void;
+
+                match &obj.raw_data_index {
+                    None => {}
+                    Some(index) => data_type = index.data_type,
                 }
-            }
-        }
 
-        // now we need to iterate yet again in order to build the start/end positions of each chunk
-        for (_, channels) in groups.iter_mut() {
-            match channels {
-                None => continue,
-                Some(channels) => {
-                    for (_, channel) in channels.iter_mut() {
-                        if channel.data_type == TdmsDataType::DAQmxRawData {
-                            continue;
-                        }
+                match &obj.daqmx_data_index {
+                    None => {}
+                    Some(index) => data_type = index.data_type,
+                }
 
-                        let mut i = 0;
-                        loop {
-                            let ChannelPositions(prev_start, prev_end) =
-                                match channel.chunk_positions.get(i) {
-                                    None => {
-                                        return Err(General(String::from(
-                                            "unable to fetch previous chunk positions",
-                                        )))
-                                    }
-                                    Some(p) => p,
-                                };
-
-                            let new_start = prev_start + chunk_size;
-                            let mut new_end = prev_end + chunk_size;
-
-                            if new_start > segment_end_pos {
-                                break;
-                            }
-
-                            if new_end > segment_end_pos {
-                                new_end = segment_end_pos;
-                            }
-
-                            channel
-                                .chunk_positions
-                                .push(ChannelPositions(new_start, new_end));
-                            i += 1;
-                        }
+                let channel = Channel {
+                    full_path: obj.object_path.clone(),
+                    group_path: group_path.to_string(),
+                    path: channel_path.to_string(),
+                    data_type,
+                    raw_data_index: obj.raw_data_index.clone(),
+                    daqmx_data_index: obj.daqmx_data_index.clone(),
+                    properties: obj.properties.clone(),
+                    // positions are calculated once the segment's full object list is known
+                    chunk_positions: vec![],
+                    string_offset_pos: None,
+                    interleaved_offset: 0,
+                };
+
+                if get_channel(&groups, group_path, channel_path).is_none() {
+                    channel_order.push((group_path.to_string(), channel_path.to_string()));
+                }
+
+                match groups.get_mut(group_path) {
+                    Some(Some(map)) => {
+                        map.insert(channel_path.to_string(), channel);
+                    }
+                    _ => {
+                        groups.insert(
+                            group_path.to_string(),
+                            Some(indexmap! {channel_path.to_string() => channel}),
+                        );
                     }
                 }
             }
         }
 
-        //
+        let chunk_size = calculate_positions(
+            &mut groups,
+            &channel_order,
+            raw_data_start,
+            segment_end_pos,
+            lead_in.table_of_contents & K_TOC_INTERLEAVED_DATA != 0,
+        );
 
         return Ok(Segment {
             lead_in,
@@ -311,6 +331,7 @@ impl Segment {
             // lead in plus offset
             end_pos: segment_end_pos,
             groups,
+            channel_order,
             chunk_size,
         });
     }
@@ -339,6 +360,54 @@ impl Segment {
         return self.lead_in.table_of_contents & K_TOC_RAW_DATA != 0;
     }
 
+    /// `truncate_to_whole_chunks` drops the raw data of an incomplete segment that comes after its
+    /// last complete chunk, so that every channel is left with the same number of values. Returns
+    /// the segment's new end position.
+    pub(crate) fn truncate_to_whole_chunks(&mut self) -> u64 {
+        self.truncate_at(self.end_pos)
+    }
+
+    /// `truncate_at` ends an incomplete segment at `end_pos` instead of where it currently ends,
+    /// dropping any raw data after the last complete chunk before that point. Returns the segment's
+    /// new end position.
+    pub(crate) fn truncate_at(&mut self, end_pos: u64) -> u64 {
+        if self.is_complete() {
+            return self.end_pos;
+        }
+
+        let raw_data_start = self.start_pos + self.lead_in.raw_data_offset;
+        let end_pos = end_pos.min(self.end_pos);
+
+        self.end_pos = match end_pos.checked_sub(raw_data_start) {
+            Some(length) if self.chunk_size > 0 => {
+                raw_data_start + length / self.chunk_size * self.chunk_size
+            }
+            _ => end_pos,
+        };
+
+        let interleaved = self.has_interleaved_data();
+        calculate_positions(
+            &mut self.groups,
+            &self.channel_order,
+            raw_data_start,
+            self.end_pos,
+            interleaved,
+        );
+
+        self.end_pos
+    }
+
+    /// is_complete returns false for a segment that is still being written, or was never finished,
+    /// which means more of its raw data might still be written to the file
+    pub fn is_complete(&self) -> bool {
+        self.lead_in.next_segment_offset != INCOMPLETE_SEGMENT_OFFSET
+            && self
+                .lead_in
+                .next_segment_offset
+                .checked_add(28 + self.start_pos)
+                == Some(self.end_pos)
+    }
+
     /// this function is not accurate unless the lead in portion of the segment has been read
     pub fn has_new_obj_list(&self) -> bool {
         return self.lead_in.table_of_contents & K_TOC_NEW_OBJ_LIST != 0;
@@ -387,10 +456,20 @@ impl LeadIn {
     /// `from_bytes` accepts a 28 byte array which represents the lead-in to a segment. This is hardcoded
     /// as there are no dynamic lengths in this portion of a segment
     pub fn from_bytes(lead_in: &[u8; 28]) -> Result<Self, TdmsError> {
+        LeadIn::from_tagged_bytes(lead_in, "5444536d")
+    }
+
+    /// `from_index_bytes` accepts the lead in of a `.tdms_index` segment, identical to the segment's
+    /// lead in apart from its tag
+    pub fn from_index_bytes(lead_in: &[u8; 28]) -> Result<Self, TdmsError> {
+        LeadIn::from_tagged_bytes(lead_in, "54445368")
+    }
+
+    fn from_tagged_bytes(lead_in: &[u8; 28], expected_tag: &str) -> Result<Self, TdmsError> {
         let mut tag: [u8; 4] = [0; 4];
         tag.clone_from_slice(&lead_in[0..4]);
 
-        if hex::encode(tag) != String::from("5444536d") {
+        if hex::encode(tag) != expected_tag {
             return Err(InvalidSegment());
         }
 
@@ -423,9 +502,15 @@ impl LeadIn {
         raw_offset.clone_from_slice(&lead_in[20..28]);
 
         let raw_data_offset = if table_of_contents & K_TOC_BIG_ENDIAN != 0 {
-            u64::from_be_bytes(raw_offset) + 28
+            u64::from_be_bytes(raw_offset)
         } else {
-            u64::from_le_bytes(raw_offset) + 28
+            u64::from_le_bytes(raw_offset)
+        };
+
+        // a corrupt offset can be large enough to overflow once the lead in is added
+        let raw_data_offset = match raw_data_offset.checked_add(28) {
+            None => return Err(InvalidSegment()),
+            Some(offset) => offset,
         };
 
         return Ok(LeadIn {
@@ -453,6 +538,9 @@ pub struct MetadataObject {
     pub object_path: String,
     pub raw_data_index: Option<RawDataIndex>,
     pub daqmx_data_index: Option<DAQmxDataIndex>,
+    // true when the object's raw data index was written as 0x00000000, meaning the index is the same
+    // as the one used for this object in the previous segment. 0xFFFFFFFF means no raw data instead
+    pub raw_data_index_matches_previous: bool,
     pub properties: Vec<MetadataProperty>,
 }
 
@@ -460,10 +548,7 @@ impl Metadata {
     /// from_reader accepts an open reader and attempts to read metadata from the currently selected
     /// segment. Note that you must have read the segment's lead in information completely before
     /// attempting to use this function
-    pub fn from_reader<R: Read + Seek>(
-        endianness: Endianness,
-        r: &mut R,
-    ) -> Result<Self, TdmsError> {
+    pub fn from_reader<R: Read>(endianness: Endianness, r: &mut R) -> Result<Self, TdmsError> {
         let mut buf: [u8; 4] = [0; 4];
         r.read(&mut buf)?;
 
@@ -490,8 +575,7 @@ impl Metadata {
                 }
             };
 
-            let mut path = vec![0; length];
-            r.read_exact(&mut path)?;
+            let path = read_bytes(r, length)?;
 
             // all strings are UTF8 encoded in TDMS files, most prefixed by the length attribute
             // like above
@@ -528,6 +612,8 @@ impl Metadata {
                 }
             }
 
+            let raw_data_index_matches_previous = first_byte == 0x0000000;
+
             r.read_exact(&mut buf)?;
             let num_of_properties: u32 = to_u32!(buf, endianness);
 
@@ -544,6 +630,7 @@ impl Metadata {
                 object_path,
                 raw_data_index,
                 daqmx_data_index,
+                raw_data_index_matches_previous,
                 properties,
             });
         }
@@ -555,7 +642,7 @@ impl Metadata {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RawDataIndex {
     pub data_type: TdmsDataType,
     pub array_dimension: u32, // should only ever be 1
@@ -564,10 +651,7 @@ pub struct RawDataIndex {
 }
 
 impl RawDataIndex {
-    pub fn from_reader<R: Read + Seek>(
-        endianness: Endianness,
-        r: &mut R,
-    ) -> Result<Self, TdmsError> {
+    pub fn from_reader<R: Read>(endianness: Endianness, r: &mut R) -> Result<Self, TdmsError> {
         let mut buf: [u8; 4] = [0; 4];
 
         // now we check the data type
@@ -609,12 +693,14 @@ pub struct DAQmxDataIndex {
     pub number_of_values: u64,
     pub format_changing_size: Option<u32>,
     pub format_changing_vec: Option<Vec<FormatChangingScaler>>,
+    pub digital_line_size: Option<u32>,
+    pub digital_line_vec: Option<Vec<DigitalLineScaler>>,
     pub buffer_vec_size: u32,
     pub buffers: Vec<u32>,
 }
 
 impl DAQmxDataIndex {
-    pub fn from_reader<R: Read + Seek>(
+    pub fn from_reader<R: Read>(
         endianness: Endianness,
         r: &mut R,
         is_format_changing: bool,
@@ -637,8 +723,10 @@ impl DAQmxDataIndex {
 
         let mut buf: [u8; 4] = [0; 4];
 
-        let format_changing_size: Option<u32> = None;
-        let format_changing_vec: Option<Vec<FormatChangingScaler>> = None;
+        let mut format_changing_size: Option<u32> = None;
+        let mut format_changing_vec: Option<Vec<FormatChangingScaler>> = None;
+        let mut digital_line_size: Option<u32> = None;
+        let mut digital_line_vec: Option<Vec<DigitalLineScaler>> = None;
         if is_format_changing {
             r.read_exact(&mut buf)?;
             let changing_vec_size = to_u32!(buf, endianness);
@@ -647,6 +735,20 @@ impl DAQmxDataIndex {
             for _ in 0..changing_vec_size {
                 vec.push(FormatChangingScaler::from_reader(endianness, r)?)
             }
+
+            format_changing_size = Some(changing_vec_size);
+            format_changing_vec = Some(vec);
+        } else {
+            r.read_exact(&mut buf)?;
+            let line_vec_size = to_u32!(buf, endianness);
+
+            let mut vec: Vec<DigitalLineScaler> = vec![];
+            for _ in 0..line_vec_size {
+                vec.push(DigitalLineScaler::from_reader(endianness, r)?)
+            }
+
+            digital_line_size = Some(line_vec_size);
+            digital_line_vec = Some(vec);
         }
 
         r.read_exact(&mut buf)?;
@@ -667,10 +769,51 @@ impl DAQmxDataIndex {
             number_of_values,
             format_changing_size,
             format_changing_vec,
+            digital_line_size,
+            digital_line_vec,
             buffer_vec_size,
             buffers,
         });
     }
+
+    /// `raw_location` returns where the channel's values are found in each row of the raw buffers,
+    /// as its first scaler's buffer index, byte offset within the buffer's rows and the data type
+    /// stored there
+    pub fn raw_location(&self) -> Option<(u32, u32, TdmsDataType)> {
+        if let Some(scaler) = self.format_changing_vec.as_ref().and_then(|v| v.first()) {
+            return Some((
+                scaler.raw_buffer_index,
+                scaler.raw_byte_offset,
+                scaler.data_type,
+            ));
+        }
+
+        let scaler = self.digital_line_vec.as_ref().and_then(|v| v.first())?;
+        Some((
+            scaler.raw_buffer_index,
+            scaler.byte_offset(),
+            TdmsDataType::U8(1),
+        ))
+    }
+}
+
+/// daqmx_data_type converts the data type code of a DAQmx scaler, which uses its own numbering rather
+/// than the TDMS data type codes
+fn daqmx_data_type(code: u32) -> Result<TdmsDataType, TdmsError> {
+    match code {
+        0 => Ok(TdmsDataType::U8(1)),
+        1 => Ok(TdmsDataType::I8(1)),
+        2 => Ok(TdmsDataType::U16(2)),
+        3 => Ok(TdmsDataType::I16(2)),
+        4 => Ok(TdmsDataType::U32(4)),
+        5 => Ok(TdmsDataType::I32(4)),
+        6 => Ok(TdmsDataType::U64(8)),
+        7 => Ok(TdmsDataType::I64(8)),
+        8 => Ok(TdmsDataType::SingleFloat(4)),
+        9 => Ok(TdmsDataType::DoubleFloat(8)),
+        0xFFFFFFFF => Ok(TdmsDataType::TimeStamp(16)),
+        _ => Err(UnknownDataType()),
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -683,16 +826,11 @@ pub struct FormatChangingScaler {
 }
 
 impl FormatChangingScaler {
-    pub fn from_reader<R: Read + Seek>(
-        endianness: Endianness,
-        r: &mut R,
-    ) -> Result<Self, TdmsError> {
+    pub fn from_reader<R: Read>(endianness: Endianness, r: &mut R) -> Result<Self, TdmsError> {
         let mut buf: [u8; 4] = [0; 4];
         r.read_exact(&mut buf)?;
 
-        let data_type = to_i32!(buf, endianness);
-
-        let data_type = TdmsDataType::try_from(data_type)?;
+        let data_type = daqmx_data_type(to_u32!(buf, endianness))?;
 
         r.read_exact(&mut buf)?;
         let raw_buffer_index = to_u32!(buf, endianness);
@@ -716,6 +854,56 @@ impl FormatChangingScaler {
     }
 }
 
+#[derive(Debug, Clone)]
+/// `DigitalLineScaler` locates a single digital line within the rows of a DAQmx raw buffer. Lines
+/// are single bits, `raw_bit_offset` counts bits from the start of the row
+pub struct DigitalLineScaler {
+    pub data_type: TdmsDataType,
+    pub raw_buffer_index: u32,
+    pub raw_bit_offset: u32,
+    pub sample_format_bitmap: u8,
+    pub scale_id: u32,
+}
+
+impl DigitalLineScaler {
+    pub fn from_reader<R: Read>(endianness: Endianness, r: &mut R) -> Result<Self, TdmsError> {
+        let mut buf: [u8; 4] = [0; 4];
+        r.read_exact(&mut buf)?;
+
+        let data_type = daqmx_data_type(to_u32!(buf, endianness))?;
+
+        r.read_exact(&mut buf)?;
+        let raw_buffer_index = to_u32!(buf, endianness);
+
+        r.read_exact(&mut buf)?;
+        let raw_bit_offset = to_u32!(buf, endianness);
+
+        let mut bitmap: [u8; 1] = [0; 1];
+        r.read_exact(&mut bitmap)?;
+
+        r.read_exact(&mut buf)?;
+        let scale_id = to_u32!(buf, endianness);
+
+        Ok(DigitalLineScaler {
+            data_type,
+            raw_buffer_index,
+            raw_bit_offset,
+            sample_format_bitmap: bitmap[0],
+            scale_id,
+        })
+    }
+
+    /// `byte_offset` returns the offset of the byte holding the line within the buffer's rows
+    pub fn byte_offset(&self) -> u32 {
+        self.raw_bit_offset / 8
+    }
+
+    /// `bit` returns the position of the line within its byte, counting from the least significant
+    pub fn bit(&self) -> u8 {
+        (self.raw_bit_offset % 8) as u8
+    }
+}
+
 #[derive(Debug, Clone)]
 /// `MetadataProperty` is a key/value pair associated with a `MetadataObject`
 pub struct MetadataProperty {
@@ -728,10 +916,7 @@ impl MetadataProperty {
     /// from_reader accepts an open reader and attempts to read metadata properties from the currently
     /// selected segment and metadata object. Note that you must have read the metadata object's lead
     /// in information prior to using this function
-    pub fn from_reader<R: Read + Seek>(
-        endianness: Endianness,
-        r: &mut R,
-    ) -> Result<Self, TdmsError> {
+    pub fn from_reader<R: Read>(endianness: Endianness, r: &mut R) -> Result<Self, TdmsError> {
         let mut buf: [u8; 4] = [0; 4];
         r.read_exact(&mut buf)?;
 
@@ -747,8 +932,7 @@ impl MetadataProperty {
             }
         };
 
-        let mut name = vec![0; length];
-        r.read_exact(&mut name)?;
+        let name = read_bytes(r, length)?;
 
         // all strings are UTF8 encoded in TDMS files, most prefixed by the length attribute
         // like above
@@ -776,30 +960,208 @@ impl MetadataProperty {
     }
 }
 
-fn rem_quotes(value: &str) -> &str {
-    let mut original = value.chars();
-    let mut chars = value.clone().chars().peekable();
+/// calculate_positions sets the start and end positions of every chunk of raw data for each channel
+/// in the segment, returning the size in bytes of a single chunk. Channels are laid out in the order
+/// of the segment's object list, either one after the other or interleaved value by value.
+fn calculate_positions(
+    groups: &mut IndexMap<GroupPath, Option<IndexMap<ChannelPath, Channel>>>,
+    channel_order: &[(GroupPath, ChannelPath)],
+    raw_data_start: u64,
+    segment_end_pos: u64,
+    interleaved: bool,
+) -> u64 {
+    let mut chunk_size: u64 = 0;
+    // size of a single row of values when the data is interleaved
+    let mut interleaved_total_size: u64 = 0;
+    let mut interleaved_values: u64 = 0;
+    // every DAQmx channel describes the same raw buffers, they are only counted once
+    let mut daqmx_chunk_size: u64 = 0;
+
+    for (group_path, path) in channel_order {
+        let channel = match get_channel_mut(groups, group_path, path) {
+            None => continue,
+            Some(c) => c,
+        };
+        // inherited channels still carry the positions from the segment they were read in
+        channel.chunk_positions.clear();
+        channel.string_offset_pos = None;
+        channel.interleaved_offset = 0;
+
+        // DAQmx channels share raw buffers that make up the whole chunk. Each buffer holds a row per
+        // value, and a channel's scaler points at its value's position within the row
+        if let Some(index) = &channel.daqmx_data_index {
+            let (buffer, byte_offset, data_type) = match index.raw_location() {
+                None => continue,
+                Some(location) => location,
+            };
+
+            let buffer = buffer as usize;
+            let width = match index.buffers.get(buffer) {
+                None => continue,
+                Some(w) => *w as u64,
+            };
+
+            let buffer_start: u64 = index.buffers[..buffer]
+                .iter()
+                .map(|w| *w as u64 * index.number_of_values)
+                .sum();
+            let size = TdmsDataType::get_size(data_type) as u64;
+            let start_pos = raw_data_start + buffer_start + byte_offset as u64;
+
+            channel.interleaved_offset = width.saturating_sub(size);
+            channel.chunk_positions.push(ChannelPositions(
+                start_pos,
+                start_pos + width * index.number_of_values - channel.interleaved_offset,
+            ));
+
+            daqmx_chunk_size = index
+                .buffers
+                .iter()
+                .map(|w| *w as u64 * index.number_of_values)
+                .sum();
+            continue;
+        }
+
+        let index = match &channel.raw_data_index {
+            None => continue,
+            Some(index) => index,
+        };
+
+        let type_size = TdmsDataType::get_size(index.data_type) as u64;
+
+        if interleaved {
+            let start_pos = raw_data_start + interleaved_total_size;
+            channel
+                .chunk_positions
+                .push(ChannelPositions(start_pos, start_pos));
+
+            interleaved_total_size += type_size;
+            interleaved_values = index.number_of_values;
+            continue;
+        }
+
+        let data_pos = raw_data_start + chunk_size;
+
+        match index.number_of_bytes {
+            Some(number_of_bytes) if index.data_type == TdmsDataType::String => {
+                // string data starts with an offset for each value
+                let offsets_end = data_pos + index.number_of_values * 4;
+                channel.string_offset_pos = Some(ChannelPositions(data_pos, offsets_end));
+                channel
+                    .chunk_positions
+                    .push(ChannelPositions(offsets_end, data_pos + number_of_bytes));
+
+                chunk_size += number_of_bytes;
+            }
+            _ => {
+                let size = type_size * index.array_dimension as u64 * index.number_of_values;
+                channel
+                    .chunk_positions
+                    .push(ChannelPositions(data_pos, data_pos + size));
+
+                chunk_size += size;
+            }
+        }
+    }
+
+    if interleaved {
+        chunk_size = interleaved_total_size * interleaved_values;
+
+        for (group_path, path) in channel_order {
+            let channel = match get_channel_mut(groups, group_path, path) {
+                None => continue,
+                Some(c) => c,
+            };
+            // DAQmx buffers are laid out the same way whether or not the segment is interleaved
+            if channel.daqmx_data_index.is_some() {
+                continue;
+            }
+
+            let size = TdmsDataType::get_size(channel.raw_data_type()) as u64;
 
-    match chars.peek() {
-        None => (),
-        Some(first) => {
-            if first.to_string() == "'" {
-                original.next();
+            // offset tells the iterator how many bytes to move to the next value
+            channel.interleaved_offset = interleaved_total_size - size;
+
+            match channel.chunk_positions.get_mut(0) {
+                None => (),
+                Some(positions) => {
+                    positions.1 = positions.0 + chunk_size - channel.interleaved_offset
+                }
             }
         }
     }
 
-    let mut reversed = chars.rev().peekable();
-    match reversed.peek() {
-        None => (),
-        Some(last) => {
-            if last.to_string() == "'" {
-                original.next_back();
+    chunk_size += daqmx_chunk_size;
+
+    // now we repeat the first chunk's positions for every chunk written in the segment, the last
+    // chunk might be cut short if the segment itself is
+    let raw_data_size = segment_end_pos.saturating_sub(raw_data_start);
+    let chunks = match chunk_size {
+        0 => 0,
+        _ => raw_data_size.div_ceil(chunk_size),
+    };
+
+    for (group_path, path) in channel_order {
+        let channel = match get_channel_mut(groups, group_path, path) {
+            None => continue,
+            Some(c) => c,
+        };
+        let ChannelPositions(first_start, first_end) = match channel.chunk_positions.pop() {
+            None => continue,
+            Some(p) => p,
+        };
+
+        let type_size = TdmsDataType::get_size(channel.raw_data_type()) as u64;
+        let stride = type_size + channel.interleaved_offset;
+
+        for i in 0..chunks {
+            let start = first_start + i * chunk_size;
+            if start >= segment_end_pos {
+                break;
+            }
+
+            let mut end = first_end + i * chunk_size;
+
+            // only keep whole values of a chunk that was cut short
+            if end > segment_end_pos {
+                end = match channel.data_type {
+                    TdmsDataType::String => segment_end_pos,
+                    _ if segment_end_pos < start + type_size => start,
+                    _ => {
+                        start + (segment_end_pos - start - type_size) / stride * stride + type_size
+                    }
+                };
+            }
+
+            if end > start {
+                channel.chunk_positions.push(ChannelPositions(start, end));
             }
         }
     }
 
-    original.as_str()
+    chunk_size
+}
+
+fn get_channel_mut<'a>(
+    groups: &'a mut IndexMap<GroupPath, Option<IndexMap<ChannelPath, Channel>>>,
+    group_path: &str,
+    path: &str,
+) -> Option<&'a mut Channel> {
+    match groups.get_mut(group_path) {
+        Some(Some(channels)) => channels.get_mut(path),
+        _ => None,
+    }
+}
+
+fn get_channel<'a>(
+    groups: &'a IndexMap<GroupPath, Option<IndexMap<ChannelPath, Channel>>>,
+    group_path: &str,
+    path: &str,
+) -> Option<&'a Channel> {
+    match groups.get(group_path) {
+        Some(Some(channels)) => channels.get(path),
+        _ => None,
+    }
 }
 
 #[macro_export]
@@ -1,7 +1,20 @@
-use crate::segment::Segment;
-use crate::TDMSFile;
+use crate::channel_values::{ChannelValue, ChannelValues};
+use crate::data_type::{Extended, TDMSValue, TdmsComplex, TdmsDataType, TdmsTimestamp};
+use crate::fixed_point;
+use crate::fixed_point::{FixedPoint, FixedPointEncoding, FixedPointWord};
+use crate::group_rows::UnevenLengths;
+use crate::index;
+use crate::object_path::ObjectPath;
+use crate::reader::SharedReader;
+use crate::recovery::{SkipReason, SkippedRegion};
+use crate::segment::{Endianness, Segment};
+use crate::writer::TdmsWriter;
+use crate::{TDMSFile, TdmsError};
+use fixed::types::{I16F16, I32F32, I3F5, I4F4};
 use std::fs::File;
-use std::path::Path;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 #[test]
 fn can_read_lead_in() {
@@ -83,3 +96,2079 @@ fn can_read_groups_channels() {
 
     assert_eq!(file.segments.len(), 2);
 }
+
+/// builds a unique path in the system temp directory so tests can run in parallel
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tdms_{}_{}.tdms", name, std::process::id()))
+}
+
+#[test]
+fn can_write_and_read_segments() {
+    let path = temp_path("write_read");
+
+    let mut writer = TdmsWriter::create(&path).expect("unable to create writer");
+    writer.set_file_property("name", "writer test");
+    writer.set_group_property("Group", "description", "a group");
+    writer.set_channel_property("Group", "Voltage", "unit_string", "V");
+    writer
+        .add_channel_data("Group", "Voltage", &[1.0f64, 2.0, 3.0])
+        .expect("unable to add data");
+    writer
+        .add_channel_data("Group", "Count", &[10i32, 20, 30])
+        .expect("unable to add data");
+    writer
+        .add_channel_data(
+            "Group",
+            "Label",
+            &[String::from("one"), String::from("two")],
+        )
+        .expect("unable to add data");
+    writer.write_segment().expect("unable to write segment");
+
+    writer
+        .add_channel_data("Group", "Voltage", &[4.0f64, 5.0])
+        .expect("unable to add data");
+    writer.flush().expect("unable to flush writer");
+    drop(writer);
+
+    let file = match TDMSFile::from_path(&path) {
+        Ok(f) => f,
+        Err(e) => panic!("{:?}", e),
+    };
+
+    assert_eq!(file.segments.len(), 2);
+    assert_eq!(file.groups(), vec![String::from("Group")]);
+
+    let channels = file.channels("Group");
+    assert_eq!(channels.len(), 3);
+
+    let voltage: Vec<f64> = file
+        .channel_data_double_float(channels.get("Voltage").unwrap())
+        .expect("unable to read channel")
+        .collect();
+    assert_eq!(voltage, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    let count: Vec<i32> = file
+        .channel_data_i32(channels.get("Count").unwrap())
+        .expect("unable to read channel")
+        .collect();
+    assert_eq!(count, vec![10, 20, 30]);
+
+    let labels: Vec<String> = file
+        .channel_data_string(channels.get("Label").unwrap())
+        .expect("unable to read channel")
+        .collect();
+    assert_eq!(labels, vec![String::from("one"), String::from("two")]);
+
+    let root = &file.segments[0].metadata.as_ref().unwrap().objects[0];
+    assert_eq!(root.object_path, "/");
+    assert_eq!(root.properties[0].name, "name");
+    assert_eq!(
+        root.properties[0].value.value,
+        Some(b"writer test".to_vec())
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_write_and_read_big_endian() {
+    let path = temp_path("write_read_be");
+
+    let file = File::create(&path).unwrap();
+    let mut writer = TdmsWriter::new(file, Endianness::Big);
+    writer.set_channel_property("Group", "Wave", "wf_increment", 0.5f64);
+    writer
+        .add_channel_data("Group", "Wave", &[1.5f64, -2.5])
+        .expect("unable to add data");
+    writer.flush().expect("unable to flush writer");
+    drop(writer);
+
+    let file = match TDMSFile::from_path(&path) {
+        Ok(f) => f,
+        Err(e) => panic!("{:?}", e),
+    };
+
+    let channels = file.channels("Group");
+    let channel = channels.get("Wave").unwrap();
+    assert_eq!(
+        channel.properties[0].value.value,
+        Some(0.5f64.to_be_bytes().to_vec())
+    );
+
+    let wave: Vec<f64> = file
+        .channel_data_double_float(channel)
+        .expect("unable to read channel")
+        .collect();
+    assert_eq!(wave, vec![1.5, -2.5]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_read_channel_without_data_in_segment() {
+    let path = temp_path("no_data_segment");
+
+    let mut writer = TdmsWriter::create(&path).expect("unable to create writer");
+    writer
+        .add_channel_data("Group", "A", &[1.0f64, 2.0])
+        .unwrap();
+    writer.add_channel_data("Group", "B", &[1i32, 2]).unwrap();
+    writer.write_segment().unwrap();
+
+    // B is listed with a property change only, its raw data index is 0xFFFFFFFF
+    writer.set_channel_property("Group", "B", "offset", 1.0f64);
+    writer.add_channel_data("Group", "A", &[3.0f64]).unwrap();
+    writer.write_segment().unwrap();
+
+    // a metadata only segment, no raw data at all
+    writer.set_channel_property("Group", "B", "offset", 2.0f64);
+    writer.write_segment().unwrap();
+
+    writer.add_channel_data("Group", "B", &[3i32]).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let file = match TDMSFile::from_path(&path) {
+        Ok(f) => f,
+        Err(e) => panic!("{:?}", e),
+    };
+
+    assert_eq!(file.segments.len(), 4);
+    let channels = file.channels("Group");
+
+    let a: Vec<f64> = file
+        .channel_data_double_float(channels.get("A").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(a, vec![1.0, 2.0, 3.0]);
+
+    let b: Vec<i32> = file
+        .channel_data_i32(channels.get("B").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(b, vec![1, 2, 3]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_append_segments() {
+    let path = temp_path("append");
+
+    let mut writer = TdmsWriter::create(&path).expect("unable to create writer");
+    writer.set_channel_property("Group", "A", "unit_string", "V");
+    writer
+        .add_channel_data("Group", "A", &[1.0f64, 2.0])
+        .unwrap();
+    writer.add_channel_data("Group", "B", &[1i32, 2]).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    // same layout as the last segment, nothing but raw data should be written
+    let mut writer = TdmsWriter::append(&path).expect("unable to open writer");
+    writer.add_channel_data("Group", "B", &[3i32, 4]).unwrap();
+    writer
+        .add_channel_data("Group", "A", &[3.0f64, 4.0])
+        .unwrap();
+    writer.write_segment().unwrap();
+
+    // same channels but A's index changes, so only A is listed
+    writer.add_channel_data("Group", "A", &[5.0f64]).unwrap();
+    writer.add_channel_data("Group", "B", &[5i32, 6]).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    // a new object list containing only A, whose index matches the previous one
+    let mut writer = TdmsWriter::append(&path).expect("unable to open writer");
+    writer.add_channel_data("Group", "A", &[6.0f64]).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let file = match TDMSFile::from_path(&path) {
+        Ok(f) => f,
+        Err(e) => panic!("{:?}", e),
+    };
+
+    assert_eq!(file.segments.len(), 4);
+
+    assert!(file.segments[1].metadata.is_none());
+    assert!(!file.segments[1].has_new_obj_list());
+
+    let metadata = file.segments[2].metadata.as_ref().unwrap();
+    assert!(!file.segments[2].has_new_obj_list());
+    assert_eq!(metadata.objects.len(), 1);
+    assert_eq!(metadata.objects[0].object_path, "/'Group'/'A'");
+
+    let metadata = file.segments[3].metadata.as_ref().unwrap();
+    assert!(file.segments[3].has_new_obj_list());
+    assert_eq!(metadata.objects.len(), 1);
+    assert!(metadata.objects[0].raw_data_index_matches_previous);
+
+    let channels = file.channels("Group");
+    let a: Vec<f64> = file
+        .channel_data_double_float(channels.get("A").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(a, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+    let b: Vec<i32> = file
+        .channel_data_i32(channels.get("B").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(b, vec![1, 2, 3, 4, 5, 6]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_append_after_incomplete_segment() {
+    let path = temp_path("append_incomplete");
+    let index_path = index::index_path(&path);
+
+    let segment = |values: &[f64]| -> Vec<u8> {
+        let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+        writer.add_channel_data("Group", "A", values).unwrap();
+        writer.into_inner().unwrap()
+    };
+
+    // an index written before the second segment was started, which no longer lists every segment
+    let first = segment(&[1.0, 2.0, 3.0]);
+    std::fs::write(&path, &first).unwrap();
+    TDMSFile::from_path(&path).unwrap().write_index().unwrap();
+
+    // the second segment's writer stopped after two and a half more chunks of a single value each
+    let mut partial = segment(&[4.0]);
+    partial[12..20].copy_from_slice(&[0xFF; 8]);
+    partial.extend_from_slice(&5.0f64.to_le_bytes());
+    partial.extend_from_slice(&6.0f64.to_le_bytes()[..4]);
+    std::fs::write(&path, [first.as_slice(), partial.as_slice()].concat()).unwrap();
+
+    let mut writer = TdmsWriter::append(&path).expect("unable to open writer");
+    writer.add_channel_data("Group", "A", &[7.0f64]).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+    std::fs::remove_file(&index_path).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    assert_eq!(file.segments.len(), 3);
+    assert!(file.segments.iter().all(|s| s.is_complete()));
+    assert!(!file.segments[2].has_new_obj_list());
+
+    let channels = file.channels("Group");
+    let a: Vec<f64> = file
+        .channel_data_double_float(channels.get("A").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(a, vec![1.0, 2.0, 3.0, 4.0, 5.0, 7.0]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_read_channel_data_be() {
+    let file = match TDMSFile::from_path(Path::new("data/big_endian.tdms")) {
+        Ok(f) => f,
+        Err(e) => panic!("{:?}", e),
+    };
+
+    let channels = file.channels("Measured Data");
+    let amplitude: Vec<f64> = file
+        .channel_data_double_float(channels.get("Amplitude sweep").unwrap())
+        .unwrap()
+        .collect();
+
+    assert_eq!(amplitude.len(), 3500);
+}
+
+#[test]
+fn can_write_and_read_index() {
+    let path = temp_path("index");
+    let index_path = index::index_path(&path);
+
+    let mut writer = TdmsWriter::create(&path).expect("unable to create writer");
+    writer
+        .add_channel_data("Group", "A", &[1.0f64, 2.0])
+        .unwrap();
+    writer.write_segment().unwrap();
+    writer.add_channel_data("Group", "A", &[3.0f64]).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    file.write_index().expect("unable to write index");
+
+    let index = std::fs::read(&index_path).unwrap();
+    assert_eq!(&index[0..4], b"TDSh");
+
+    let file_len = std::fs::metadata(&path).unwrap().len();
+    let segments = index::read_index(&index_path, file_len)
+        .expect("unable to read index")
+        .expect("index should be up to date");
+
+    assert_eq!(segments.len(), 2);
+    for (indexed, read) in segments.iter().zip(file.segments.iter()) {
+        assert_eq!(indexed.start_pos, read.start_pos);
+        assert_eq!(indexed.end_pos, read.end_pos);
+    }
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Group");
+    let a: Vec<f64> = file
+        .channel_data_double_float(channels.get("A").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(a, vec![1.0, 2.0, 3.0]);
+
+    // appending makes the index stale, the file itself must be read instead
+    let mut writer = TdmsWriter::append(&path).expect("unable to open writer");
+    writer.add_channel_data("Group", "A", &[4.0f64]).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let file_len = std::fs::metadata(&path).unwrap().len();
+    assert!(index::read_index(&index_path, file_len).unwrap().is_none());
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    assert_eq!(file.segments.len(), 3);
+
+    // truncating the file part way through its last segment makes the index stale as well, even
+    // though the segments it lists still reach the end of the file
+    file.write_index().expect("unable to write index");
+    let file_len = file.segments[2].end_pos - 4;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_len(file_len)
+        .unwrap();
+    assert!(index::read_index(&index_path, file_len).unwrap().is_none());
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&index_path).unwrap();
+}
+
+#[test]
+fn can_follow_file_being_written() {
+    let path = temp_path("follow");
+
+    let segment = |values: &[f64]| -> Vec<u8> {
+        let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+        writer.add_channel_data("Group", "A", values).unwrap();
+        writer.into_inner().unwrap()
+    };
+
+    let first = segment(&[1.0, 2.0, 3.0]);
+    let second = segment(&[4.0, 5.0, 6.0, 7.0]);
+    let third = segment(&[8.0]);
+
+    // the second segment is still being written - its length is unknown and its last value and a
+    // half are missing
+    let mut partial = second.clone();
+    partial[12..20].copy_from_slice(&[0xFF; 8]);
+    partial.truncate(partial.len() - 12);
+    std::fs::write(&path, [first.as_slice(), partial.as_slice()].concat()).unwrap();
+
+    let mut file = TDMSFile::follow(&path).expect("unable to follow file");
+    assert_eq!(file.segments.len(), 2);
+    assert!(!file.segments[1].is_complete());
+
+    let channels = file.channels("Group");
+    let mut data = file
+        .channel_data_double_float(channels.get("A").unwrap())
+        .unwrap();
+    let values: Vec<f64> = data.by_ref().collect();
+    assert_eq!(values, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    let position = data.position();
+    drop(data);
+
+    std::fs::write(&path, [first, second, third].concat()).unwrap();
+
+    // the incomplete segment is read again along with the new one
+    assert_eq!(file.refresh().unwrap(), 2);
+    assert_eq!(file.segments.len(), 3);
+    assert!(file.segments[1].is_complete());
+
+    let channels = file.channels("Group");
+    let mut data = file
+        .channel_data_double_float(channels.get("A").unwrap())
+        .unwrap();
+    data.resume(position).unwrap();
+    let values: Vec<f64> = data.collect();
+    assert_eq!(values, vec![6.0, 7.0, 8.0]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_recover_damaged_file() {
+    let path = temp_path("recover");
+
+    let segment = |a: &[f64], b: &[i32]| -> Vec<u8> {
+        let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+        writer.add_channel_data("Group", "A", a).unwrap();
+        writer.add_channel_data("Group", "B", b).unwrap();
+        writer.into_inner().unwrap()
+    };
+
+    let first = segment(&[1.0, 2.0], &[1, 2]);
+    let garbage = b"not a segment".to_vec();
+    let third = segment(&[3.0, 4.0], &[3, 4]);
+
+    // a segment holding two chunks, cut short partway through the second
+    let mut last = segment(&[5.0, 6.0], &[5, 6]);
+    let chunk = last[last.len() - 24..].to_vec();
+    let next_segment_offset = u64::from_le_bytes(last[12..20].try_into().unwrap()) + 24;
+    last[12..20].copy_from_slice(&next_segment_offset.to_le_bytes());
+    last.extend_from_slice(&chunk[..14]);
+
+    let contents = [first.clone(), garbage.clone(), third.clone(), last.clone()].concat();
+    let file_len = contents.len() as u64;
+    std::fs::write(&path, contents).unwrap();
+
+    assert!(TDMSFile::from_path(&path).is_err());
+
+    let garbage_start = first.len() as u64;
+    let garbage_end = garbage_start + garbage.len() as u64;
+
+    let (file, skipped) = TDMSFile::recover(&path, false).expect("unable to recover file");
+    assert_eq!(file.segments.len(), 1);
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].start_pos, garbage_start);
+    assert_eq!(skipped[0].end_pos, file_len);
+    assert!(matches!(skipped[0].reason, SkipReason::InvalidSegment(_)));
+
+    let (file, skipped) = TDMSFile::recover(&path, true).expect("unable to recover file");
+    assert_eq!(file.segments.len(), 3);
+    assert_eq!(
+        skipped,
+        vec![
+            SkippedRegion {
+                start_pos: garbage_start,
+                end_pos: garbage_end,
+                reason: skipped[0].reason.clone(),
+            },
+            SkippedRegion {
+                start_pos: file_len - 14,
+                end_pos: file_len,
+                reason: SkipReason::PartialChunk,
+            },
+        ]
+    );
+
+    let channels = file.channels("Group");
+    let a: Vec<f64> = file
+        .channel_data_double_float(channels.get("A").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(a, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+    let b: Vec<i32> = file
+        .channel_data_i32(channels.get("B").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(b, vec![1, 2, 3, 4, 5, 6]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_recover_past_incomplete_segment() {
+    let path = temp_path("recover_incomplete");
+
+    let segment = |a: &[f64], b: &[i32]| -> Vec<u8> {
+        let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+        writer.add_channel_data("Group", "A", a).unwrap();
+        writer.add_channel_data("Group", "B", b).unwrap();
+        writer.into_inner().unwrap()
+    };
+
+    let first = segment(&[1.0, 2.0], &[1, 2]);
+
+    // a segment that was never finished, with a chunk and a half written after its first chunk
+    let mut incomplete = segment(&[3.0], &[3]);
+    incomplete[12..20].copy_from_slice(&[0xFF; 8]);
+    let chunk = [
+        4.0f64.to_le_bytes().as_slice(),
+        4i32.to_le_bytes().as_slice(),
+    ]
+    .concat();
+    incomplete.extend_from_slice(&chunk);
+    incomplete.extend_from_slice(&chunk[..6]);
+
+    let third = segment(&[5.0], &[5]);
+
+    // a segment whose first object path claims to be nearly 4GB long
+    let mut corrupt = segment(&[6.0], &[6]);
+    corrupt[32..36].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+
+    let contents = [first.clone(), incomplete.clone(), third.clone(), corrupt].concat();
+    let file_len = contents.len() as u64;
+    std::fs::write(&path, contents).unwrap();
+
+    let third_start = (first.len() + incomplete.len()) as u64;
+    let corrupt_start = third_start + third.len() as u64;
+
+    let (file, skipped) = TDMSFile::recover(&path, true).expect("unable to recover file");
+    assert_eq!(file.segments.len(), 3);
+    assert_eq!(file.segments[1].end_pos, third_start - 6);
+    assert_eq!(skipped.len(), 2);
+    assert_eq!(
+        skipped[0],
+        SkippedRegion {
+            start_pos: third_start - 6,
+            end_pos: third_start,
+            reason: SkipReason::PartialChunk,
+        }
+    );
+    assert_eq!(skipped[1].start_pos, corrupt_start);
+    assert_eq!(skipped[1].end_pos, file_len);
+    match &skipped[1].reason {
+        SkipReason::InvalidSegment(e) => assert!(e.contains("runs past the end of the metadata")),
+        reason => panic!("unexpected reason {:?}", reason),
+    }
+
+    let channels = file.channels("Group");
+    let a: Vec<f64> = file
+        .channel_data_double_float(channels.get("A").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(a, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    let b: Vec<i32> = file
+        .channel_data_i32(channels.get("B").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(b, vec![1, 2, 3, 4, 5]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_recover_past_overflowing_raw_data_offset() {
+    let path = temp_path("recover_overflow");
+
+    let segment = |values: &[f64]| -> Vec<u8> {
+        let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+        writer.add_channel_data("Group", "A", values).unwrap();
+        writer.into_inner().unwrap()
+    };
+
+    let first = segment(&[1.0, 2.0]);
+    let third = segment(&[5.0]);
+
+    // offsets overflowing when the lead in is added, and when added to the segment's position
+    for raw_data_offset in [u64::MAX, u64::MAX - 28] {
+        let mut corrupt = segment(&[3.0, 4.0]);
+        corrupt[20..28].copy_from_slice(&raw_data_offset.to_le_bytes());
+
+        let contents = [first.as_slice(), corrupt.as_slice(), third.as_slice()].concat();
+        std::fs::write(&path, contents).unwrap();
+
+        assert!(TDMSFile::from_path(&path).is_err());
+
+        let (file, skipped) = TDMSFile::recover(&path, true).expect("unable to recover file");
+        assert_eq!(file.segments.len(), 2);
+        assert_eq!(
+            skipped,
+            vec![SkippedRegion {
+                start_pos: first.len() as u64,
+                end_pos: (first.len() + corrupt.len()) as u64,
+                reason: SkipReason::InvalidSegment(TdmsError::InvalidSegment().to_string()),
+            }]
+        );
+
+        let channels = file.channels("Group");
+        let a: Vec<f64> = file
+            .channel_data_double_float(channels.get("A").unwrap())
+            .unwrap()
+            .collect();
+        assert_eq!(a, vec![1.0, 2.0, 5.0]);
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_read_typed_channel_data() {
+    let path = temp_path("typed");
+
+    let mut writer = TdmsWriter::create(&path).expect("unable to create writer");
+    writer
+        .add_channel_data("Group", "I16", &[-1i16, 2])
+        .unwrap();
+    writer
+        .add_channel_data("Group", "Bool", &[true, false, true])
+        .unwrap();
+    writer
+        .add_channel_data(
+            "Group",
+            "Timestamp",
+            &[TdmsTimestamp(3_000_000_000, 1 << 63)],
+        )
+        .unwrap();
+    writer
+        .add_channel_data("Group", "String", &[String::from("a"), String::from("bcd")])
+        .unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Group");
+
+    let values: Vec<i16> = file
+        .channel_data(channels.get("I16").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(values, vec![-1, 2]);
+
+    let values: Vec<bool> = file
+        .channel_data(channels.get("Bool").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(values, vec![true, false, true]);
+
+    let values: Vec<TdmsTimestamp> = file
+        .channel_data(channels.get("Timestamp").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[0].0, 3_000_000_000);
+    assert_eq!(values[0].1, 1 << 63);
+
+    let values: Vec<String> = file
+        .channel_data(channels.get("String").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(values, vec!["a", "bcd"]);
+
+    match file.channel_data::<f64>(channels.get("I16").unwrap()) {
+        Err(TdmsError::DataTypeMismatch(TdmsDataType::DoubleFloat(_), TdmsDataType::I16(_))) => (),
+        Err(e) => panic!("unexpected error {:?}", e),
+        Ok(_) => panic!("reading an I16 channel as f64 should fail"),
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_read_any_channel_data() {
+    let path = temp_path("any");
+
+    let complex = [
+        TdmsComplex {
+            re: 1.0f64,
+            im: -1.0,
+        },
+        TdmsComplex { re: 2.5, im: 0.5 },
+    ];
+
+    let mut writer = TdmsWriter::create(&path).expect("unable to create writer");
+    writer
+        .add_channel_data("Group", "U8", &[1u8, 2, 3])
+        .unwrap();
+    writer
+        .add_channel_data("Group", "String", &[String::from("a")])
+        .unwrap();
+    writer
+        .add_channel_data("Group", "Complex", &complex)
+        .unwrap();
+    writer
+        .add_channel_data("Group", "After", &[7.0f32, 8.0])
+        .unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Group");
+    let values = |name: &str| file.any_channel_data(channels.get(name).unwrap()).unwrap();
+
+    assert_eq!(values("U8"), ChannelValues::U8(vec![1, 2, 3]));
+    assert_eq!(
+        values("String"),
+        ChannelValues::String(vec![String::from("a")])
+    );
+    assert_eq!(
+        values("Complex"),
+        ChannelValues::ComplexDoubleFloat(complex.to_vec())
+    );
+    assert_eq!(values("After"), ChannelValues::SingleFloat(vec![7.0, 8.0]));
+    assert_eq!(values("After").len(), 2);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// rewrites a single chunk segment written by `TdmsWriter` so that its raw data is interleaved,
+/// `sizes` holding the size of a value of each channel in order
+fn interleave(mut segment: Vec<u8>, sizes: &[usize], values: usize) -> Vec<u8> {
+    let toc = u32::from_le_bytes(segment[4..8].try_into().unwrap()) | (1 << 5);
+    segment[4..8].copy_from_slice(&toc.to_le_bytes());
+
+    let raw_data_offset = u64::from_le_bytes(segment[20..28].try_into().unwrap()) as usize;
+    let raw_data = segment.split_off(28 + raw_data_offset);
+
+    let mut channel_start = 0;
+    let mut channels: Vec<&[u8]> = vec![];
+    for size in sizes {
+        channels.push(&raw_data[channel_start..channel_start + size * values]);
+        channel_start += size * values;
+    }
+
+    for i in 0..values {
+        for (channel, size) in channels.iter().zip(sizes) {
+            segment.extend_from_slice(&channel[i * size..(i + 1) * size]);
+        }
+    }
+
+    segment
+}
+
+#[test]
+fn can_read_interleaved_complex_data() {
+    let path = temp_path("complex");
+
+    let double = [
+        TdmsComplex { re: 1.0, im: 2.0 },
+        TdmsComplex { re: 3.0, im: 4.0 },
+        TdmsComplex { re: 5.0, im: 6.0 },
+    ];
+    let single = [
+        TdmsComplex { re: -1.0, im: -2.0 },
+        TdmsComplex { re: -3.0, im: -4.0 },
+        TdmsComplex { re: -5.0, im: -6.0 },
+    ];
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer.add_channel_data("Group", "Double", &double).unwrap();
+    writer.add_channel_data("Group", "Single", &single).unwrap();
+    writer
+        .add_channel_data("Group", "I8", &[1i8, 2, 3])
+        .unwrap();
+    let segment = writer.into_inner().unwrap();
+
+    // one contiguous segment followed by the same data interleaved
+    let contents = [segment.clone(), interleave(segment, &[16, 8, 1], 3)].concat();
+    std::fs::write(&path, contents).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Group");
+
+    let values: Vec<TdmsComplex<f64>> = file
+        .channel_data_complex_double_float(channels.get("Double").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(values, [double, double].concat());
+
+    #[cfg(feature = "num-complex")]
+    {
+        let values: Vec<num_complex::Complex<f64>> = file
+            .channel_data(channels.get("Double").unwrap())
+            .unwrap()
+            .collect();
+        assert_eq!(values[4], num_complex::Complex::new(3.0, 4.0));
+    }
+
+    let values: Vec<TdmsComplex<f32>> = file
+        .channel_data_complex_single_float(channels.get("Single").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(values, [single, single].concat());
+
+    let values: Vec<i8> = file
+        .channel_data(channels.get("I8").unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(values, vec![1, 2, 3, 1, 2, 3]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_read_extended_float_data() {
+    let values = [1.5f64, -0.1, 1e300];
+    let extended: Vec<Extended> = values.iter().map(|v| Extended::from(*v)).collect();
+
+    for (name, endianness) in [
+        ("extended_le", Endianness::Little),
+        ("extended_be", Endianness::Big),
+    ] {
+        let path = temp_path(name);
+
+        let mut writer = TdmsWriter::new(vec![], endianness);
+        writer.set_channel_property("Group", "Extended", "scale", Extended::from(0.25));
+        writer
+            .add_channel_data("Group", "Extended", &extended)
+            .unwrap();
+        std::fs::write(&path, writer.into_inner().unwrap()).unwrap();
+
+        let file = TDMSFile::from_path(&path).expect("unable to read file");
+        let channels = file.channels("Group");
+        let channel = channels.get("Extended").unwrap();
+
+        let read: Vec<f64> = file.channel_data(channel).unwrap().collect();
+        assert_eq!(read, values);
+
+        let read: Vec<Extended> = file.channel_data_extended_float(channel).unwrap().collect();
+        assert_eq!(read, extended);
+
+        let property = &channel.properties[0];
+        assert_eq!(property.value.to_native::<f64>().unwrap(), 0.25);
+        assert!(property.value.to_native::<f32>().is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn can_read_fixed_point_data() {
+    // signed, 16 bit words with 4 integer bits - values are the integer divided by 2 ^ 12
+    let words = [0x1800u64, 0xF000, 0x7FFF, 0xFFFF_FFFF_FFFF_8000];
+
+    for (name, endianness) in [("fxp_le", Endianness::Little), ("fxp_be", Endianness::Big)] {
+        let path = temp_path(name);
+
+        let mut writer = TdmsWriter::new(vec![], endianness);
+        writer.set_channel_property("Group", "FXP", fixed_point::SIGNED_PROPERTY, true);
+        writer.set_channel_property("Group", "FXP", fixed_point::WORD_LENGTH_PROPERTY, 16u8);
+        writer.set_channel_property(
+            "Group",
+            "FXP",
+            fixed_point::INTEGER_WORD_LENGTH_PROPERTY,
+            4i32,
+        );
+        let values: Vec<FixedPointWord> = words.iter().map(|w| FixedPointWord(*w)).collect();
+        writer.add_channel_data("Group", "FXP", &values).unwrap();
+        std::fs::write(&path, writer.into_inner().unwrap()).unwrap();
+
+        let file = TDMSFile::from_path(&path).expect("unable to read file");
+        let channels = file.channels("Group");
+        let channel = channels.get("FXP").unwrap();
+
+        let values: Vec<FixedPoint> = file.channel_data_fixed_point(channel).unwrap().collect();
+        let floats: Vec<f64> = values.iter().map(|v| v.to_f64()).collect();
+        assert_eq!(floats, vec![1.5, -1.0, 8.0 - 1.0 / 4096.0, -8.0]);
+
+        assert_eq!(values[0].to_fixed::<I16F16>(), Some(I16F16::from_num(1.5)));
+        assert_eq!(values[3].to_fixed::<I16F16>(), Some(I16F16::from_num(-8)));
+        assert_eq!(values[3].to_fixed::<I4F4>(), Some(I4F4::from_num(-8)));
+        // just under 8 doesn't fit in 3 integer bits
+        assert_eq!(values[2].to_fixed::<I3F5>(), None);
+
+        assert!(file.channel_data::<u64>(channel).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn can_read_fixed_point_files() {
+    // the fixtures hold the same values, written out to the NI file format by hand rather than
+    // with `TdmsWriter` so the on disk layout isn't only checked against itself. Each value takes
+    // 10 bytes with the word in the low 8, at the front of little endian values and the back of
+    // big endian ones
+    for name in ["data/fixed_point.tdms", "data/fixed_point_big_endian.tdms"] {
+        let file = TDMSFile::from_path(Path::new(name)).expect("unable to read file");
+        let channels = file.channels("Group");
+
+        // signed, 24 bit words with 8 integer bits
+        let signed = channels.get("Signed").unwrap();
+        let values: Vec<f64> = file
+            .channel_data_fixed_point(signed)
+            .unwrap()
+            .map(|v| v.to_f64())
+            .collect();
+        assert_eq!(values, vec![1.5, -2.25, 128.0 - 1.0 / 65536.0, -128.0]);
+
+        // unsigned, 12 bit words with 4 integer bits
+        let unsigned = channels.get("Unsigned").unwrap();
+        let values: Vec<f64> = file
+            .channel_data_fixed_point(unsigned)
+            .unwrap()
+            .map(|v| v.to_f64())
+            .collect();
+        assert_eq!(values, vec![1.5, 16.0 - 1.0 / 256.0, 0.0, 1.0 / 256.0]);
+    }
+}
+
+#[test]
+fn can_convert_fixed_point_without_fractional_bits() {
+    let value = |integer_word_length: i16, bits: u64| FixedPoint {
+        bits,
+        encoding: FixedPointEncoding {
+            signed: true,
+            word_length: 16,
+            integer_word_length,
+        },
+    };
+
+    // no fractional bits, the value is the stored integer
+    assert_eq!(value(16, 5).to_f64(), 5.0);
+    assert_eq!(value(16, 5).to_fixed::<I32F32>(), Some(I32F32::from_num(5)));
+    assert_eq!(
+        value(16, 0xFFFB).to_fixed::<I32F32>(),
+        Some(I32F32::from_num(-5))
+    );
+
+    // negative fractional bits, the value is the integer times a power of two
+    assert_eq!(
+        value(20, 5).to_fixed::<I32F32>(),
+        Some(I32F32::from_num(80))
+    );
+    assert_eq!(
+        value(20, 0xFFFB).to_fixed::<I32F32>(),
+        Some(I32F32::from_num(-80))
+    );
+    assert_eq!(value(48, 5).to_fixed::<I32F32>(), None);
+    assert_eq!(value(80, 5).to_fixed::<I32F32>(), None);
+    assert_eq!(value(80, 0).to_fixed::<I32F32>(), Some(I32F32::from_num(0)));
+}
+
+#[test]
+fn can_read_daqmx_raw_data() {
+    let file = TDMSFile::from_path(Path::new("data/raw.tdms")).expect("unable to read file");
+    let channels = file.channels("Layer Data");
+    assert_eq!(channels.len(), 7);
+
+    // the channels share one raw buffer, each row holding one i16 from every channel in turn
+    let first = channels.get("First  Channel").unwrap();
+    let values: Vec<i16> = file.channel_data::<i16>(first).unwrap().collect();
+    assert_eq!(values.len(), 2000);
+    assert_eq!(&values[..2], &[-603, 485]);
+    assert_eq!(values[1999], 3);
+
+    let last = channels.get("Seventh Cha").unwrap();
+    let values: Vec<i16> = file.channel_data::<i16>(last).unwrap().collect();
+    assert_eq!(values.len(), 2000);
+    assert_eq!(values[0], 16525);
+    assert_eq!(values[1999], 16629);
+
+    match file.any_channel_data(last).unwrap() {
+        ChannelValues::I16(v) => assert_eq!(v.len(), 2000),
+        v => panic!("unexpected channel values {:?}", v),
+    }
+
+    assert!(file.channel_data::<f64>(first).is_err());
+}
+
+#[test]
+fn can_read_scaled_channel_data() {
+    let path = temp_path("scaled");
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    let properties: [(&str, &str, TDMSValue); 13] = [
+        // microvolts to volts, then volts to degrees Celsius
+        ("TC", "NI_Number_Of_Scales", 2u32.into()),
+        ("TC", "NI_Scale[0]_Scale_Type", "Linear".into()),
+        ("TC", "NI_Scale[0]_Linear_Slope", 1e-6.into()),
+        ("TC", "NI_Scale[0]_Linear_Y_Intercept", 0.0.into()),
+        (
+            "TC",
+            "NI_Scale[0]_Linear_Input_Source",
+            0xFFFFFFFFu32.into(),
+        ),
+        ("TC", "NI_Scale[1]_Scale_Type", "Thermocouple".into()),
+        ("TC", "NI_Scale[1]_Thermocouple_Type", 10073i32.into()),
+        ("TC", "NI_Scale[1]_Thermocouple_Input_Source", 0u32.into()),
+        // (x + 1)^2, then a table multiplying by 10 up to 100, then its reciprocal
+        ("Chain", "NI_Scale[0]_Scale_Type", "Polynomial".into()),
+        (
+            "Chain",
+            "NI_Scale[0]_Polynomial_Coefficients_Size",
+            3u32.into(),
+        ),
+        (
+            "Chain",
+            "NI_Scale[0]_Polynomial_Coefficients[0]",
+            1.0.into(),
+        ),
+        (
+            "Chain",
+            "NI_Scale[0]_Polynomial_Coefficients[1]",
+            2.0.into(),
+        ),
+        (
+            "Chain",
+            "NI_Scale[0]_Polynomial_Coefficients[2]",
+            1.0.into(),
+        ),
+    ];
+    for (channel, name, value) in properties {
+        writer.set_channel_property("Group", channel, name, value);
+    }
+    let table: [(&str, TDMSValue); 9] = [
+        ("NI_Scale[1]_Scale_Type", "Table".into()),
+        ("NI_Scale[1]_Table_Pre_Scaled_Values_Size", 2u32.into()),
+        ("NI_Scale[1]_Table_Pre_Scaled_Values[0]", 10.0.into()),
+        ("NI_Scale[1]_Table_Pre_Scaled_Values[1]", 0.0.into()),
+        ("NI_Scale[1]_Table_Scaled_Values_Size", 2u32.into()),
+        ("NI_Scale[1]_Table_Scaled_Values[0]", 100.0.into()),
+        ("NI_Scale[1]_Table_Scaled_Values[1]", 0.0.into()),
+        ("NI_Scale[2]_Scale_Type", "Reciprocal".into()),
+        ("NI_Scale[2]_Reciprocal_Input_Source", 1u32.into()),
+    ];
+    for (name, value) in table {
+        writer.set_channel_property("Group", "Chain", name, value);
+    }
+    writer.set_channel_property("Group", "Chain", "NI_Scale[1]_Table_Input_Source", 0u32);
+    writer.set_channel_property("Group", "Scaled", "NI_Scaling_Status", "scaled");
+    writer.set_channel_property("Group", "Scaled", "NI_Scale[0]_Scale_Type", "Reciprocal");
+    writer.set_channel_property("Group", "Corrupt", "NI_Number_Of_Scales", u32::MAX);
+    writer.set_channel_property("Group", "Corrupt", "NI_Scale[0]_Scale_Type", "Reciprocal");
+
+    writer
+        .add_channel_data("Group", "TC", &[0i16, 20644, -5891])
+        .unwrap();
+    writer
+        .add_channel_data("Group", "Chain", &[0.5f64, 2.0, 10.0])
+        .unwrap();
+    writer.add_channel_data("Group", "Scaled", &[4u8]).unwrap();
+    writer.add_channel_data("Group", "Corrupt", &[4u8]).unwrap();
+    std::fs::write(&path, writer.into_inner().unwrap()).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Group");
+
+    let temperatures: Vec<f64> = file
+        .channel_data_scaled(channels.get("TC").unwrap())
+        .unwrap()
+        .collect();
+    for (t, expected) in temperatures.iter().zip([0.0, 500.0, -200.0]) {
+        assert!(
+            (t - expected).abs() < 0.1,
+            "{} isn't close to {}",
+            t,
+            expected
+        );
+    }
+
+    let mut chain = file
+        .channel_data_scaled(channels.get("Chain").unwrap())
+        .unwrap();
+    assert_eq!(chain.scaling().steps.len(), 3);
+    assert_eq!(chain.next_with_raw(), Some((0.5, 1.0 / 22.5)));
+    assert_eq!(chain.collect::<Vec<f64>>(), vec![1.0 / 90.0, 0.01]);
+
+    let mut scaled = file
+        .channel_data_scaled(channels.get("Scaled").unwrap())
+        .unwrap();
+    assert!(scaled.scaling().is_identity());
+    assert_eq!(scaled.next(), Some(4.0));
+
+    // a count beyond the scales described is an error rather than an allocation of that many
+    assert!(file
+        .channel_data_scaled(channels.get("Corrupt").unwrap())
+        .is_err());
+
+    // DAQmx channels have their first scale applied by the DAQmx scaler, it isn't in the properties
+    let file = TDMSFile::from_path(Path::new("data/raw.tdms")).expect("unable to read file");
+    let channels = file.channels("Layer Data");
+    let mut volts = file
+        .channel_data_scaled(channels.get("First  Channel").unwrap())
+        .unwrap();
+    let (raw, value) = volts.next_with_raw().unwrap();
+    assert_eq!(raw, -603.0);
+    assert!((value - raw * 3.0518509475997192e-4).abs() < 1e-12);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// digital_line_segment builds a DAQmx segment holding digital lines in a single raw buffer, each
+/// line given as its channel name and bit offset within the buffer's rows of `width` bytes
+fn digital_line_segment(lines: &[(&str, u32)], width: u32, raw: &[u8]) -> Vec<u8> {
+    let mut metadata: Vec<u8> = vec![];
+    metadata.extend_from_slice(&(lines.len() as u32 + 1).to_le_bytes());
+
+    let group = "/'Dev1'";
+    metadata.extend_from_slice(&(group.len() as u32).to_le_bytes());
+    metadata.extend_from_slice(group.as_bytes());
+    metadata.extend_from_slice(&0xFFFFFFFFu32.to_le_bytes());
+    metadata.extend_from_slice(&0u32.to_le_bytes());
+
+    for (name, bit_offset) in lines {
+        let path = format!("{}/'{}'", group, name);
+        metadata.extend_from_slice(&(path.len() as u32).to_le_bytes());
+        metadata.extend_from_slice(path.as_bytes());
+        metadata.extend_from_slice(&0x69130000u32.to_le_bytes());
+        metadata.extend_from_slice(&0xFFFFFFFFu32.to_le_bytes());
+        metadata.extend_from_slice(&1u32.to_le_bytes());
+        metadata.extend_from_slice(&(raw.len() as u64 / width as u64).to_le_bytes());
+        // a single digital line scaler - data type, buffer index, bit offset, bitmap and scale id
+        metadata.extend_from_slice(&1u32.to_le_bytes());
+        metadata.extend_from_slice(&0u32.to_le_bytes());
+        metadata.extend_from_slice(&0u32.to_le_bytes());
+        metadata.extend_from_slice(&bit_offset.to_le_bytes());
+        metadata.push(0);
+        metadata.extend_from_slice(&0u32.to_le_bytes());
+        // a single buffer
+        metadata.extend_from_slice(&1u32.to_le_bytes());
+        metadata.extend_from_slice(&width.to_le_bytes());
+        // no properties
+        metadata.extend_from_slice(&0u32.to_le_bytes());
+    }
+
+    let mut segment: Vec<u8> = b"TDSm".to_vec();
+    segment.extend_from_slice(&(1u32 << 1 | 1 << 2 | 1 << 3 | 1 << 7).to_le_bytes());
+    segment.extend_from_slice(&4713u32.to_le_bytes());
+    segment.extend_from_slice(&((metadata.len() + raw.len()) as u64).to_le_bytes());
+    segment.extend_from_slice(&(metadata.len() as u64).to_le_bytes());
+    segment.extend_from_slice(&metadata);
+    segment.extend_from_slice(raw);
+
+    segment
+}
+
+#[test]
+fn can_read_digital_line_data() {
+    let path = temp_path("digital");
+    let rows = [0b01, 0b10, 0b10, 0b00, 0b11, 0b10, 0b00, 0b00];
+    let segment = digital_line_segment(&[("line0", 0), ("line1", 1), ("line9", 9)], 2, &rows);
+    std::fs::write(&path, segment).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Dev1");
+    let line0 = channels.get("line0").unwrap();
+    let line1 = channels.get("line1").unwrap();
+    let line9 = channels.get("line9").unwrap();
+
+    let states: Vec<bool> = file.digital_line_data(line0).unwrap().collect();
+    assert_eq!(states, vec![true, false, true, false]);
+    let states: Vec<bool> = file.digital_line_data(line1).unwrap().collect();
+    assert_eq!(states, vec![false, true, true, false]);
+
+    match file.any_channel_data(line9).unwrap() {
+        ChannelValues::Boolean(v) => assert_eq!(v, vec![true, false, true, false]),
+        v => panic!("unexpected channel values {:?}", v),
+    }
+
+    let port: Vec<u64> = file
+        .digital_port_data(&[line0, line1, line9])
+        .unwrap()
+        .collect();
+    assert_eq!(port, vec![0b101, 0b010, 0b111, 0b000]);
+
+    let file = TDMSFile::from_path(Path::new("data/raw.tdms")).expect("unable to read file");
+    let channels = file.channels("Layer Data");
+    assert!(file
+        .digital_line_data(channels.get("First  Channel").unwrap())
+        .is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_read_waveform_time_axis() {
+    let path = temp_path("waveform");
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer.set_channel_property(
+        "Group",
+        "Wave",
+        "wf_start_time",
+        TdmsTimestamp(100, 1 << 63),
+    );
+    writer.set_channel_property("Group", "Wave", "wf_increment", 0.25);
+    writer.set_channel_property("Group", "Wave", "wf_start_offset", 1.0);
+    writer.set_channel_property("Group", "Wave", "wf_samples", 3i32);
+    writer
+        .add_channel_data("Group", "Wave", &[1.0f64, 2.0, 3.0])
+        .unwrap();
+    writer.add_channel_data("Group", "Plain", &[1u8]).unwrap();
+    std::fs::write(&path, writer.into_inner().unwrap()).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Group");
+    let channel = channels.get("Wave").unwrap();
+
+    let waveform = file.waveform(channel).unwrap();
+    assert_eq!(waveform.start_time, TdmsTimestamp(100, 1 << 63));
+    assert_eq!(waveform.increment, 0.25);
+    assert_eq!(waveform.samples, Some(3));
+    assert_eq!(waveform.time_of(10), TdmsTimestamp(104, 0));
+
+    let values: Vec<(TdmsTimestamp, f64)> = waveform
+        .with_values(file.channel_data::<f64>(channel).unwrap())
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            (TdmsTimestamp(101, 1 << 63), 1.0),
+            (TdmsTimestamp(101, 3 << 62), 2.0),
+            (TdmsTimestamp(102, 0), 3.0),
+        ]
+    );
+
+    assert!(file.waveform(channels.get("Plain").unwrap()).is_err());
+
+    // the waveform properties of DAQmx channels are written in a later segment than their data
+    let file = TDMSFile::from_path(Path::new("data/raw.tdms")).expect("unable to read file");
+    let channels = file.channels("Layer Data");
+    let waveform = file
+        .waveform(channels.get("First  Channel").unwrap())
+        .unwrap();
+    assert_eq!(waveform.samples, Some(1));
+    assert_eq!(waveform.start_offset, 0.0);
+    assert!(waveform.increment > 0.0);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_convert_timestamps() {
+    let unix = TdmsTimestamp(2_082_844_800, 0);
+    assert_eq!(TdmsTimestamp::UNIX_EPOCH, unix);
+    assert_eq!(unix.to_unix_nanos(), 0);
+    assert_eq!(unix.to_string(), "1970-01-01T00:00:00.000000000Z");
+    assert_eq!(
+        TdmsTimestamp::EPOCH.to_string(),
+        "1904-01-01T00:00:00.000000000Z"
+    );
+
+    let time = TdmsTimestamp::from_unix_nanos(1_654_086_600_250_000_000);
+    assert_eq!(time, TdmsTimestamp(3_736_931_400, 1 << 62));
+    assert_eq!(time.to_unix_nanos(), 1_654_086_600_250_000_000);
+    assert_eq!(time.to_string(), "2022-06-01T12:30:00.250000000Z");
+
+    let before = TdmsTimestamp::from_unix_nanos(-1);
+    assert_eq!(before.to_unix_nanos(), -1);
+    assert_eq!(before.to_string(), "1969-12-31T23:59:59.999999999Z");
+
+    let system = SystemTime::UNIX_EPOCH + Duration::from_nanos(1_654_086_600_250_000_000);
+    assert_eq!(TdmsTimestamp::try_from(system).unwrap(), time);
+    assert_eq!(SystemTime::try_from(time).unwrap(), system);
+    let system = SystemTime::UNIX_EPOCH - Duration::from_secs(10);
+    assert_eq!(
+        SystemTime::try_from(TdmsTimestamp::try_from(system).unwrap()).unwrap(),
+        system
+    );
+
+    let duration = Duration::try_from(time).unwrap();
+    assert_eq!(duration.as_secs(), 3_736_931_400);
+    assert_eq!(TdmsTimestamp::try_from(duration).unwrap(), time);
+    assert!(Duration::try_from(TdmsTimestamp(-1, 0)).is_err());
+
+    // ordering compares seconds and then fractions
+    assert!(unix < time);
+    assert!(before < unix);
+    assert!(TdmsTimestamp(1, u64::MAX) < TdmsTimestamp(2, 0));
+
+    let mut later = time + Duration::from_millis(1750);
+    assert_eq!(later, TdmsTimestamp(3_736_931_402, 0));
+    later -= Duration::from_secs(2);
+    assert_eq!(later.duration_since(time), None);
+    assert_eq!(time.duration_since(later), Some(Duration::from_millis(250)));
+    assert_eq!(later.seconds_since(time), -0.25);
+    assert_eq!(time.add_seconds(-0.25), later);
+    assert_eq!(
+        TdmsTimestamp(i64::MAX, u64::MAX).checked_add(Duration::from_nanos(1)),
+        None
+    );
+
+    #[cfg(feature = "chrono")]
+    {
+        let date: chrono::DateTime<chrono::Utc> = time.try_into().unwrap();
+        assert_eq!(date.to_rfc3339(), "2022-06-01T12:30:00.250+00:00");
+        assert_eq!(TdmsTimestamp::from(date), time);
+    }
+
+    #[cfg(feature = "time")]
+    {
+        let date = time::OffsetDateTime::try_from(time).unwrap();
+        assert_eq!(date.unix_timestamp_nanos(), 1_654_086_600_250_000_000);
+        assert_eq!(TdmsTimestamp::from(date), time);
+    }
+}
+
+#[test]
+fn can_read_typed_property_values() {
+    let file = TDMSFile::from_path(Path::new("data/raw.tdms")).expect("unable to read file");
+    let channel = file.segments[2]
+        .get_channel("Layer Data", "First  Channel")
+        .unwrap();
+    let property = |name: &str| {
+        &channel
+            .properties
+            .iter()
+            .find(|p| p.name == name)
+            .unwrap()
+            .value
+    };
+
+    assert_eq!(
+        property("NI_ChannelName").as_str().unwrap(),
+        "First  Channel"
+    );
+    assert_eq!(property("NI_ChannelName").to_string(), "First  Channel");
+    assert_eq!(property("wf_samples").as_i64().unwrap(), 1);
+    assert_eq!(property("wf_samples").as_f64().unwrap(), 1.0);
+    assert_eq!(i32::try_from(property("wf_samples")).unwrap(), 1);
+    assert_eq!(property("wf_start_offset").as_f64().unwrap(), 0.0);
+    assert!(property("wf_increment").as_i64().is_err());
+    assert!(property("wf_increment").as_str().is_err());
+    assert!(i32::try_from(property("wf_increment")).is_err());
+
+    let start_time = property("wf_start_time").as_timestamp().unwrap();
+    assert_eq!(
+        TdmsTimestamp::try_from(property("wf_start_time")).unwrap(),
+        start_time
+    );
+    assert_eq!(
+        property("wf_start_time").to_string(),
+        start_time.to_string()
+    );
+
+    let value = TDMSValue::from(true);
+    assert!(value.as_bool().unwrap());
+    assert_eq!(value.to_string(), "true");
+    assert_eq!(TDMSValue::from(u64::MAX).as_u64().unwrap(), u64::MAX);
+    assert!(TDMSValue::from(u64::MAX).as_i64().is_err());
+    assert_eq!(TDMSValue::from(-3i8).as_i64().unwrap(), -3);
+    assert!(TDMSValue::from(-3i8).as_u64().is_err());
+    assert_eq!(TDMSValue::from(2.5f32).to_string(), "2.5");
+    let complex = TdmsComplex {
+        re: 1.0f64,
+        im: -2.0,
+    };
+    assert_eq!(TDMSValue::from(complex).to_string(), "1-2i");
+}
+
+#[test]
+fn can_read_merged_properties() {
+    let path = temp_path("merged_properties");
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer.set_file_property("name", "first");
+    writer.set_file_property("author", "tdms");
+    writer.set_group_property("Group's", "description", "readings");
+    writer.set_channel_property("Group's", "A", "unit_string", "V");
+    writer.add_channel_data("Group's", "A", &[1.0f64]).unwrap();
+    writer.write_segment().unwrap();
+
+    // later segments only list what changed
+    writer.set_file_property("name", "second");
+    writer.set_channel_property("Group's", "A", "unit_string", "mV");
+    writer.set_channel_property("Group's", "A", "NI_ChannelName", "A");
+    writer.add_channel_data("Group's", "A", &[2.0f64]).unwrap();
+    std::fs::write(&path, writer.into_inner().unwrap()).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+
+    let properties = file.file_properties();
+    assert_eq!(properties.len(), 2);
+    assert_eq!(properties["name"].as_str().unwrap(), "second");
+    assert_eq!(properties["author"].as_str().unwrap(), "tdms");
+
+    let properties = file.group_properties("Group's");
+    assert_eq!(properties["description"].as_str().unwrap(), "readings");
+
+    let properties = file.channel_properties("Group's", "A");
+    let names: Vec<&String> = properties.keys().collect();
+    assert_eq!(names, vec!["unit_string", "NI_ChannelName"]);
+    assert_eq!(properties["unit_string"].as_str().unwrap(), "mV");
+
+    assert!(file.channel_properties("Group's", "B").is_empty());
+    assert!(file.group_properties("Other").is_empty());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_parse_object_paths() {
+    assert_eq!(ObjectPath::parse("/").unwrap(), ObjectPath::Root);
+    assert_eq!(
+        ObjectPath::parse("/'Group'").unwrap(),
+        ObjectPath::group("Group")
+    );
+
+    let path = ObjectPath::parse("/'Temp/Inlet'/'Operator''s note'").unwrap();
+    assert_eq!(path.group_name(), Some("Temp/Inlet"));
+    assert_eq!(path.channel_name(), Some("Operator's note"));
+    assert_eq!(path.to_string(), "/'Temp/Inlet'/'Operator''s note'");
+    assert_eq!(ObjectPath::channel("''", "/").to_string(), "/''''''/'/'");
+    assert_eq!(
+        "/''''''/'/'".parse::<ObjectPath>().unwrap(),
+        ObjectPath::channel("''", "/")
+    );
+
+    for invalid in [
+        "",
+        "Group",
+        "/Group",
+        "/'Group",
+        "/'Group'x",
+        "/'A'/'B'/'C'",
+        "//",
+    ] {
+        assert!(ObjectPath::parse(invalid).is_err(), "{} parsed", invalid);
+    }
+
+    let path = temp_path("object_paths");
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer.set_group_property("Temp/Inlet", "description", "inlet");
+    writer
+        .add_channel_data("Temp/Inlet", "Operator's note", &[1u8, 2])
+        .unwrap();
+    std::fs::write(&path, writer.into_inner().unwrap()).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    assert_eq!(file.groups(), vec!["Temp/Inlet"]);
+    let channels = file.channels("Temp/Inlet");
+    let channel = channels.get("Operator's note").unwrap();
+    assert_eq!(
+        ObjectPath::parse(&channel.full_path).unwrap(),
+        ObjectPath::channel("Temp/Inlet", "Operator's note")
+    );
+    let values: Vec<u8> = file.channel_data(channel).unwrap().collect();
+    assert_eq!(values, vec![1, 2]);
+    assert_eq!(file.group_properties("Temp/Inlet").len(), 1);
+    drop(file);
+
+    // a segment listing a malformed path can't be read, recovery skips it
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer.add_channel_data("Group", "A", &[1u8, 2]).unwrap();
+    let mut contents = writer.into_inner().unwrap();
+    let malformed = contents
+        .windows(12)
+        .position(|w| w == b"/'Group'/'A'")
+        .unwrap();
+    contents[malformed + 8] = b'x';
+    std::fs::write(&path, &contents).unwrap();
+
+    let err = TDMSFile::from_path(&path).unwrap_err();
+    assert!(err.to_string().contains("invalid object path /'Group'x'A'"));
+
+    let (file, skipped) = TDMSFile::recover(&path, true).expect("unable to recover file");
+    assert!(file.segments.is_empty());
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].start_pos, 0);
+    assert_eq!(skipped[0].end_pos, contents.len() as u64);
+    assert!(matches!(skipped[0].reason, SkipReason::InvalidSegment(_)));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_iterate_channel_data_results() {
+    let path = temp_path("try_iter");
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer
+        .add_channel_data("Group", "Values", &[1.5f64, 2.5, 3.5])
+        .unwrap();
+    writer
+        .add_channel_data("Group", "Labels", &[String::from("ab"), String::from("cd")])
+        .unwrap();
+    let mut bytes = writer.into_inner().unwrap();
+    std::fs::write(&path, &bytes).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Group");
+    let values = channels.get("Values").unwrap();
+    let labels = channels.get("Labels").unwrap();
+
+    let mut iter = file.channel_data::<f64>(values).unwrap();
+    let read: Result<Vec<f64>, TdmsError> = iter.try_iter().collect();
+    assert_eq!(read.unwrap(), vec![1.5, 2.5, 3.5]);
+    assert!(iter.try_next().is_none());
+
+    // make the second string end before the first
+    let offsets_pos = labels.string_offset_pos.unwrap().0 as usize;
+    bytes[offsets_pos + 4..offsets_pos + 8].copy_from_slice(&1u32.to_le_bytes());
+    std::fs::write(&path, &bytes).unwrap();
+
+    let mut iter = file.channel_data::<String>(labels).unwrap();
+    let mut results = iter.try_iter();
+    assert_eq!(results.next().unwrap().unwrap(), "ab");
+    assert!(matches!(
+        results.next(),
+        Some(Err(TdmsError::CorruptChannelData(_)))
+    ));
+
+    // a string cut short is read again when retried, rather than its offset being skipped
+    bytes[offsets_pos + 4..offsets_pos + 8].copy_from_slice(&4u32.to_le_bytes());
+    std::fs::write(&path, &bytes[..offsets_pos + 11]).unwrap();
+
+    let mut iter = file.channel_data::<String>(labels).unwrap();
+    assert_eq!(iter.try_next().unwrap().unwrap(), "ab");
+    assert!(matches!(
+        iter.try_next(),
+        Some(Err(TdmsError::TruncatedChannelData()))
+    ));
+    std::fs::write(&path, &bytes).unwrap();
+    assert_eq!(iter.try_next().unwrap().unwrap(), "cd");
+
+    // cut the file short after the first value
+    let values_pos = values.chunk_positions[0].0 as usize;
+    std::fs::write(&path, &bytes[..values_pos + 12]).unwrap();
+
+    let mut iter = file.channel_data::<f64>(values).unwrap();
+    let results: Vec<Result<f64, TdmsError>> = iter.try_iter().collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(*results[0].as_ref().unwrap(), 1.5);
+    assert!(matches!(results[1], Err(TdmsError::TruncatedChannelData())));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// string_chunks_segment builds a segment holding `chunks` chunks of the same strings
+fn string_chunks_segment(strings: &[&str], chunks: usize) -> Vec<u8> {
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    let strings: Vec<String> = strings.iter().map(|s| s.to_string()).collect();
+    writer
+        .add_channel_data("Group", "Labels", &strings)
+        .unwrap();
+    let mut segment = writer.into_inner().unwrap();
+
+    // offsets followed by the strings make up the chunk, which the segment ends with
+    let chunk_size = strings.len() * 4 + strings.iter().map(|s| s.len()).sum::<usize>();
+    let chunk = segment[segment.len() - chunk_size..].to_vec();
+    for _ in 1..chunks {
+        segment.extend_from_slice(&chunk);
+    }
+
+    let next_segment_offset = u64::from_le_bytes(segment[12..20].try_into().unwrap())
+        + ((chunks - 1) * chunk_size) as u64;
+    segment[12..20].copy_from_slice(&next_segment_offset.to_le_bytes());
+
+    segment
+}
+
+#[test]
+fn can_read_string_chunks() {
+    let path = temp_path("string_chunks");
+    let contents = [
+        string_chunks_segment(&["ab", "cde"], 2),
+        string_chunks_segment(&["f"], 1),
+    ]
+    .concat();
+    std::fs::write(&path, contents).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    assert_eq!(file.channel_length("Group", "Labels"), 5);
+
+    let channels = file.channels("Group");
+    let labels = channels.get("Labels").unwrap();
+
+    let mut iter = file.channel_data::<String>(labels).unwrap();
+    let values: Result<Vec<String>, TdmsError> = iter.try_iter().collect();
+    assert_eq!(values.unwrap(), vec!["ab", "cde", "ab", "cde", "f"]);
+
+    // a position within the second chunk resumes from the same string
+    let mut iter = file.channel_data::<String>(labels).unwrap();
+    assert_eq!(iter.by_ref().take(3).count(), 3);
+    assert_eq!(iter.sample_index(), 3);
+    let position = iter.position();
+
+    let mut resumed = file.channel_data::<String>(labels).unwrap();
+    resumed.resume(position).unwrap();
+    assert_eq!(resumed.collect::<Vec<String>>(), vec!["cde", "f"]);
+
+    // seeking works out the position of any string from its chunk's offsets
+    let mut iter = file.channel_data::<String>(labels).unwrap();
+    iter.seek_to_sample(3).unwrap();
+    assert_eq!(iter.next().unwrap(), "cde");
+    iter.seek_to_sample(2).unwrap();
+    assert_eq!(iter.sample_index(), 2);
+    assert_eq!(iter.next().unwrap(), "ab");
+    assert_eq!(iter.nth(1).unwrap(), "f");
+
+    assert_eq!(iter.read_range(1, 3).unwrap(), vec!["cde", "ab", "cde"]);
+    assert_eq!(iter.read_range(2, 10).unwrap(), vec!["ab", "cde", "f"]);
+    let mut values = vec![String::new(); 2];
+    iter.seek_to_sample(3).unwrap();
+    assert_eq!(iter.read_into(&mut values).unwrap(), 2);
+    assert_eq!(values, vec!["cde", "f"]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_bulk_read_channel_data() {
+    let path = temp_path("bulk_read");
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Big);
+    writer
+        .add_channel_data("Group", "Values", &[1.0f64, 2.0, 3.0])
+        .unwrap();
+    writer.write_segment().unwrap();
+    writer
+        .add_channel_data("Group", "Values", &[4.0f64, 5.0])
+        .unwrap();
+    writer
+        .add_channel_data("Group", "Labels", &[String::from("a"), String::from("bc")])
+        .unwrap();
+    let big_endian = writer.into_inner().unwrap();
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer
+        .add_channel_data("Group", "Values", &[6.0f64, 7.0])
+        .unwrap();
+    writer.add_channel_data("Group", "I8", &[1i8, 2]).unwrap();
+    let interleaved = interleave(writer.into_inner().unwrap(), &[8, 1], 2);
+    std::fs::write(&path, [big_endian, interleaved].concat()).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Group");
+    let values = channels.get("Values").unwrap();
+
+    let mut iter = file.channel_data::<f64>(values).unwrap();
+    assert_eq!(iter.next(), Some(1.0));
+
+    // reads stop at the end of the slice, and carry on across segments and into interleaved data
+    let mut buf = [0.0; 4];
+    assert_eq!(iter.read_into(&mut buf).unwrap(), 4);
+    assert_eq!(buf, [2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(iter.read_into(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[..2], &[6.0, 7.0]);
+    assert_eq!(iter.read_into(&mut buf).unwrap(), 0);
+
+    let all = file
+        .channel_data::<f64>(values)
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(all, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+
+    let labels = file
+        .channel_data::<String>(channels.get("Labels").unwrap())
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(labels, vec![String::from("a"), String::from("bc")]);
+
+    let file = TDMSFile::from_path(Path::new("data/raw.tdms")).expect("unable to read file");
+    let channels = file.channels("Layer Data");
+    let first = channels.get("First  Channel").unwrap();
+    let values: Vec<i16> = file.channel_data::<i16>(first).unwrap().collect();
+    let all = file.channel_data::<i16>(first).unwrap().read_all().unwrap();
+    assert_eq!(all, values);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_seek_to_sample() {
+    let path = temp_path("seek");
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer
+        .add_channel_data("Group", "Values", &[0.0f64, 1.0, 2.0])
+        .unwrap();
+    writer
+        .add_channel_data("Group", "Labels", &[String::from("a"), String::from("bc")])
+        .unwrap();
+    writer.write_segment().unwrap();
+    writer
+        .add_channel_data("Group", "Values", &[3.0f64, 4.0])
+        .unwrap();
+    writer
+        .add_channel_data("Group", "Labels", &[String::from("def"), String::from("g")])
+        .unwrap();
+    let contiguous = writer.into_inner().unwrap();
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer
+        .add_channel_data("Group", "Values", &[5.0f64, 6.0, 7.0])
+        .unwrap();
+    writer
+        .add_channel_data("Group", "I8", &[1i8, 2, 3])
+        .unwrap();
+    let interleaved = interleave(writer.into_inner().unwrap(), &[8, 1], 3);
+    std::fs::write(&path, [contiguous, interleaved].concat()).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Group");
+    let values = channels.get("Values").unwrap();
+
+    let mut iter = file.channel_data::<f64>(values).unwrap();
+    for n in [6, 0, 3, 7, 2, 5] {
+        iter.seek_to_sample(n).unwrap();
+        assert_eq!(iter.sample_index(), n);
+        assert_eq!(iter.next(), Some(n as f64));
+    }
+
+    iter.seek_to_sample(8).unwrap();
+    assert_eq!(iter.next(), None);
+    assert!(iter.seek_to_sample(9).is_err());
+    assert_eq!(iter.next(), None);
+
+    let mut iter = file.channel_data::<f64>(values).unwrap();
+    assert_eq!(iter.nth(1), Some(1.0));
+    assert_eq!(iter.nth(3), Some(5.0));
+    assert_eq!(iter.sample_index(), 6);
+    assert_eq!(iter.nth(2), None);
+
+    assert_eq!(iter.read_range(2, 4).unwrap(), vec![2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(iter.read_range(6, 10).unwrap(), vec![6.0, 7.0]);
+
+    let labels = channels.get("Labels").unwrap();
+    let mut iter = file.channel_data::<String>(labels).unwrap();
+    assert_eq!(iter.read_range(1, 2).unwrap(), vec!["bc", "def"]);
+    assert_eq!(iter.nth(0), Some(String::from("g")));
+    iter.seek_to_sample(1).unwrap();
+    assert_eq!(iter.next(), Some(String::from("bc")));
+
+    let file = TDMSFile::from_path(Path::new("data/raw.tdms")).expect("unable to read file");
+    let channels = file.channels("Layer Data");
+    let last = channels.get("Seventh Cha").unwrap();
+    let values: Vec<i16> = file.channel_data::<i16>(last).unwrap().collect();
+    let mut iter = file.channel_data::<i16>(last).unwrap();
+    assert_eq!(iter.nth(1500), Some(values[1500]));
+    assert_eq!(iter.read_range(998, 3).unwrap(), &values[998..1001]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_list_channel_chunks() {
+    let path = temp_path("chunks");
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer
+        .add_channel_data("Group", "Values", &[0.0f64, 1.0, 2.0])
+        .unwrap();
+    writer.write_segment().unwrap();
+    writer.set_channel_property("Group", "Values", "unit", "V");
+    writer.write_segment().unwrap();
+    writer
+        .add_channel_data("Group", "Values", &[3.0f64, 4.0])
+        .unwrap();
+    writer.add_channel_data("Group", "I8", &[1i8, 2]).unwrap();
+    let segments = writer.into_inner().unwrap();
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer
+        .add_channel_data("Group", "Values", &[5.0f64])
+        .unwrap();
+    writer.add_channel_data("Group", "I8", &[3i8]).unwrap();
+    let interleaved = interleave(writer.into_inner().unwrap(), &[8, 1], 1);
+    let contents = [segments, interleaved].concat();
+    std::fs::write(&path, &contents).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    assert_eq!(file.channel_length("Group", "Values"), 6);
+    assert_eq!(file.channel_length("Group", "I8"), 3);
+    assert_eq!(file.channel_length("Group", "Missing"), 0);
+
+    // the property only segment holds none of the channel's data
+    let chunks = file.channel_chunks("Group", "Values");
+    let summary: Vec<(usize, std::ops::Range<u64>, u64)> = chunks
+        .iter()
+        .map(|c| (c.segment_index, c.samples.clone(), c.stride))
+        .collect();
+    assert_eq!(summary, vec![(0, 0..3, 8), (2, 3..5, 8), (3, 5..6, 9)]);
+
+    for chunk in &chunks {
+        let offset = chunk.offset as usize;
+        let value = f64::from_le_bytes(contents[offset..offset + 8].try_into().unwrap());
+        assert_eq!(value, chunk.samples.start as f64);
+    }
+
+    let file = TDMSFile::from_path(Path::new("data/raw.tdms")).expect("unable to read file");
+    assert_eq!(file.channel_length("Layer Data", "First  Channel"), 2000);
+    let chunks = file.channel_chunks("Layer Data", "Seventh Cha");
+    assert!(chunks.iter().all(|c| c.stride == 14));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_read_time_range() {
+    let path = temp_path("time_range");
+    let start = TdmsTimestamp::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer.set_channel_property("Group", "Values", "wf_start_time", start);
+    writer.set_channel_property("Group", "Values", "wf_increment", 0.5);
+    writer.set_channel_property("Group", "Values", "wf_samples", 4i32);
+    writer
+        .add_channel_data("Group", "Values", &[0i32, 1, 2, 3])
+        .unwrap();
+    writer.write_segment().unwrap();
+    writer.set_channel_property("Group", "Values", "wf_samples", 6i32);
+    writer
+        .add_channel_data("Group", "Values", &[4i32, 5])
+        .unwrap();
+    writer.write_segment().unwrap();
+    // a second waveform, acquired ten seconds after the first
+    writer.set_channel_property(
+        "Group",
+        "Values",
+        "wf_start_time",
+        start + Duration::from_secs(10),
+    );
+    writer
+        .add_channel_data("Group", "Values", &[6i32, 7, 8])
+        .unwrap();
+    writer.add_channel_data("Group", "Other", &[1u8]).unwrap();
+    writer.set_channel_property("Group", "NaN", "wf_start_time", start);
+    writer.set_channel_property("Group", "NaN", "wf_increment", f64::NAN);
+    writer.set_channel_property("Group", "NaN", "wf_samples", 1i32);
+    writer.add_channel_data("Group", "NaN", &[1u8]).unwrap();
+    std::fs::write(&path, writer.into_inner().unwrap()).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Group");
+    let channel = channels.get("Values").unwrap();
+
+    let values: Vec<(TdmsTimestamp, i32)> = file
+        .read_time_range(
+            channel,
+            start + Duration::from_secs(1),
+            start + Duration::from_secs(11),
+        )
+        .unwrap();
+    let expected: Vec<(TdmsTimestamp, i32)> = [(1000, 2), (1500, 3), (2000, 4), (2500, 5)]
+        .into_iter()
+        .chain([(10000, 6), (10500, 7)])
+        .map(|(ms, v)| (start + Duration::from_millis(ms), v))
+        .collect();
+    assert_eq!(values, expected);
+
+    let values: Vec<(TdmsTimestamp, i32)> = file
+        .read_time_range(channel, start - Duration::from_secs(5), start)
+        .unwrap();
+    assert!(values.is_empty());
+
+    let values: Vec<(TdmsTimestamp, i32)> = file
+        .read_time_range(
+            channel,
+            start + Duration::from_millis(10250),
+            start + Duration::from_secs(60),
+        )
+        .unwrap();
+    assert_eq!(
+        values.iter().map(|(_, v)| *v).collect::<Vec<i32>>(),
+        vec![7, 8]
+    );
+
+    let other = channels.get("Other").unwrap();
+    assert!(file.read_time_range::<u8>(other, start, start).is_err());
+    let nan = channels.get("NaN").unwrap();
+    assert!(file.read_time_range::<u8>(nan, start, start).is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_iterate_group_rows() {
+    let path = temp_path("group_rows");
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer
+        .add_channel_data("Group", "Values", &[1.0f64, 2.0])
+        .unwrap();
+    writer
+        .add_channel_data("Group", "Labels", &[String::from("a"), String::from("b")])
+        .unwrap();
+    writer.add_channel_data("Group", "I8", &[1i8, 2]).unwrap();
+    writer.add_channel("Group", "Empty");
+    writer.write_segment().unwrap();
+    writer
+        .add_channel_data("Group", "Values", &[3.0f64])
+        .unwrap();
+    writer.add_channel_data("Group", "I8", &[3i8]).unwrap();
+    writer.add_channel_data("Other", "Values", &[9u8]).unwrap();
+    std::fs::write(&path, writer.into_inner().unwrap()).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+
+    let rows = file.group_rows("Group", UnevenLengths::Stop).unwrap();
+    assert_eq!(rows.channels(), &["Values", "Labels", "I8"]);
+    let rows: Vec<Vec<Option<ChannelValue>>> = rows.map(|r| r.unwrap()).collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(
+        rows[1],
+        vec![
+            Some(ChannelValue::DoubleFloat(2.0)),
+            Some(ChannelValue::String(String::from("b"))),
+            Some(ChannelValue::I8(2)),
+        ]
+    );
+
+    let rows: Vec<Vec<Option<ChannelValue>>> = file
+        .group_rows("Group", UnevenLengths::PadWithNone)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(
+        rows[2],
+        vec![
+            Some(ChannelValue::DoubleFloat(3.0)),
+            None,
+            Some(ChannelValue::I8(3)),
+        ]
+    );
+
+    let rows: Vec<Result<Vec<Option<ChannelValue>>, TdmsError>> = file
+        .group_rows("Group", UnevenLengths::Error)
+        .unwrap()
+        .collect();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[1].is_ok());
+    assert!(rows[2].is_err());
+
+    // rows line up even when the channels split their values differently across segments
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    writer
+        .add_channel_data("Group", "A", &[1i32, 2, 3])
+        .unwrap();
+    writer.add_channel_data("Group", "B", &[10i32]).unwrap();
+    writer.write_segment().unwrap();
+    writer.add_channel_data("Group", "A", &[4i32]).unwrap();
+    writer
+        .add_channel_data("Group", "B", &[20i32, 30, 40])
+        .unwrap();
+    std::fs::write(&path, writer.into_inner().unwrap()).unwrap();
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+
+    let rows: Vec<Vec<Option<ChannelValue>>> = file
+        .group_rows("Group", UnevenLengths::Error)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(
+        rows[1],
+        vec![Some(ChannelValue::I32(2)), Some(ChannelValue::I32(20))]
+    );
+    assert_eq!(
+        rows[3],
+        vec![Some(ChannelValue::I32(4)), Some(ChannelValue::I32(40))]
+    );
+
+    // a failed read of a digital line is returned rather than taken as the end of the line
+    let segment = digital_line_segment(&[("line0", 0), ("line1", 1)], 1, &[0b01, 0b10, 0b11]);
+    std::fs::write(&path, &segment).unwrap();
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    std::fs::write(&path, &segment[..segment.len() - 1]).unwrap();
+
+    let rows: Vec<Result<Vec<Option<ChannelValue>>, TdmsError>> = file
+        .group_rows("Dev1", UnevenLengths::Stop)
+        .unwrap()
+        .collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(
+        *rows[1].as_ref().unwrap(),
+        vec![
+            Some(ChannelValue::Boolean(false)),
+            Some(ChannelValue::Boolean(true)),
+        ]
+    );
+    assert!(matches!(rows[2], Err(TdmsError::TruncatedChannelData())));
+
+    let file = TDMSFile::from_path(Path::new("data/raw.tdms")).expect("unable to read file");
+    let mut rows = file.group_rows("Layer Data", UnevenLengths::Error).unwrap();
+    assert_eq!(rows.channels().len(), 7);
+    let first = rows.next().unwrap().unwrap();
+    assert_eq!(first[0], Some(ChannelValue::I16(-603)));
+    assert_eq!(first[6], Some(ChannelValue::I16(16525)));
+    assert_eq!(rows.map(|r| r.unwrap()).count(), 1999);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_share_one_reader_across_channels() {
+    let path = temp_path("shared_reader");
+
+    let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+    for i in 0..50u32 {
+        let values: Vec<u32> = (0..100).map(|v| i * 1000 + v).collect();
+        writer
+            .add_channel_data("Group", &format!("Channel {}", i), &values)
+            .unwrap();
+    }
+    let bytes = writer.into_inner().unwrap();
+    std::fs::write(&path, &bytes).unwrap();
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    let channels = file.channels("Group");
+
+    // every iterator is open at once and read in turns, each through the same file
+    let mut iters = vec![];
+    for channel in channels.values() {
+        iters.push(file.channel_data::<u32>(channel).unwrap());
+    }
+
+    for v in 0..100 {
+        for (i, iter) in iters.iter_mut().enumerate() {
+            assert_eq!(iter.next(), Some(i as u32 * 1000 + v));
+        }
+    }
+    assert!(iters.iter_mut().all(|i| i.next().is_none()));
+
+    let channel = channels.get("Channel 7").unwrap();
+    let values = file
+        .channel_data_with_reader::<u32, _>(channel, std::io::Cursor::new(&bytes))
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(values, (7000..7100).collect::<Vec<u32>>());
+
+    let mut first = SharedReader::new(std::io::Cursor::new(vec![1u8, 2, 3, 4]));
+    let mut second = first.clone();
+    second.seek(SeekFrom::End(-1)).unwrap();
+
+    let mut buf = [0u8; 2];
+    first.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [1, 2]);
+    assert_eq!(second.read(&mut buf).unwrap(), 1);
+    assert_eq!(buf[0], 4);
+    first.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [3, 4]);
+    assert!(second.seek(SeekFrom::Current(-5)).is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_read_channels_listed_across_groups() {
+    let path = temp_path("mixed_groups");
+
+    let segment = |a: f64, b: i32, c: f64| {
+        let mut writer = TdmsWriter::new(vec![], Endianness::Little);
+        writer.add_channel_data("G1", "A", &[a, a + 1.0]).unwrap();
+        writer.add_channel_data("G2", "B", &[b, b + 1]).unwrap();
+        writer.add_channel_data("G1", "C", &[c, c + 1.0]).unwrap();
+        writer.into_inner().unwrap()
+    };
+
+    // the second segment inherits the first one's list without C, which it appends
+    let mut first = TdmsWriter::new(vec![], Endianness::Little);
+    first.add_channel_data("G1", "A", &[0.0f64]).unwrap();
+    first.add_channel_data("G2", "B", &[0i32]).unwrap();
+    let mut second = segment(1.0, 10, 100.0);
+    let toc = u32::from_le_bytes(second[4..8].try_into().unwrap()) & !(1 << 2);
+    second[4..8].copy_from_slice(&toc.to_le_bytes());
+
+    let contents = [first.into_inner().unwrap(), second, segment(2.0, 20, 200.0)].concat();
+    std::fs::write(&path, contents).unwrap();
+
+    let values = |file: &TDMSFile, group: &str, channel: &str| -> Vec<f64> {
+        let channels = file.channels(group);
+        file.channel_data_scaled(channels.get(channel).unwrap())
+            .unwrap()
+            .collect()
+    };
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    assert_eq!(values(&file, "G1", "A"), vec![0.0, 1.0, 2.0, 2.0, 3.0]);
+    assert_eq!(values(&file, "G2", "B"), vec![0.0, 10.0, 11.0, 20.0, 21.0]);
+    assert_eq!(values(&file, "G1", "C"), vec![100.0, 101.0, 200.0, 201.0]);
+
+    // appending with the same layout reuses the object list in its original order
+    let mut writer = TdmsWriter::append(&path).expect("unable to open writer");
+    writer.add_channel_data("G1", "A", &[4.0f64]).unwrap();
+    writer.add_channel_data("G2", "B", &[40i32]).unwrap();
+    writer.add_channel_data("G1", "C", &[400.0f64]).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let file = TDMSFile::from_path(&path).expect("unable to read file");
+    assert!(!file.segments[3].has_new_obj_list());
+    assert_eq!(values(&file, "G2", "B")[5], 40.0);
+    assert_eq!(values(&file, "G1", "C")[4], 400.0);
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,268 @@
+use crate::data_type::TdmsTimestamp;
+use crate::{General, TdmsError};
+use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// seconds from the TDMS epoch, 1904-01-01, to the Unix epoch, 1970-01-01
+const UNIX_EPOCH_SECONDS: i64 = 2_082_844_800;
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+impl TdmsTimestamp {
+    /// the TDMS epoch, 1904-01-01 00:00:00 UTC
+    pub const EPOCH: TdmsTimestamp = TdmsTimestamp(0, 0);
+    /// the Unix epoch, 1970-01-01 00:00:00 UTC
+    pub const UNIX_EPOCH: TdmsTimestamp = TdmsTimestamp(UNIX_EPOCH_SECONDS, 0);
+
+    /// `from_unix_nanos` returns the timestamp a number of nanoseconds from the Unix epoch. It takes
+    /// an `i64`, the type clocks and most time libraries count nanoseconds in, whose range of about
+    /// 292 years either side of 1970 always fits in a timestamp - see `to_unix_nanos` for the reverse
+    pub fn from_unix_nanos(nanos: i64) -> Self {
+        let nanos = nanos as i128;
+
+        TdmsTimestamp(
+            (nanos.div_euclid(NANOS_PER_SECOND) + UNIX_EPOCH_SECONDS as i128) as i64,
+            nanos_to_fraction(nanos.rem_euclid(NANOS_PER_SECOND) as u32),
+        )
+    }
+
+    /// `to_unix_nanos` returns the number of nanoseconds from the Unix epoch to the timestamp,
+    /// rounded to the nearest nanosecond. Timestamps span billions of years either side of 1904,
+    /// far more nanoseconds than an `i64` holds, so unlike `from_unix_nanos` this needs an `i128`.
+    /// Timestamps created by `from_unix_nanos` always convert back to a value within `i64`
+    pub fn to_unix_nanos(&self) -> i128 {
+        (self.0 as i128 - UNIX_EPOCH_SECONDS as i128) * NANOS_PER_SECOND
+            + fraction_to_nanos(self.1) as i128
+    }
+
+    /// `checked_add` returns the timestamp `duration` later, or `None` if that overflows
+    pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+        Some(from_units(
+            to_units(self).checked_add(duration_units(duration)?)?,
+        ))
+    }
+
+    /// `checked_sub` returns the timestamp `duration` earlier, or `None` if that overflows
+    pub fn checked_sub(&self, duration: Duration) -> Option<Self> {
+        Some(from_units(
+            to_units(self).checked_sub(duration_units(duration)?)?,
+        ))
+    }
+
+    /// `add_seconds` returns the timestamp a possibly fractional or negative number of seconds
+    /// later, saturating at the earliest and latest representable timestamps
+    pub fn add_seconds(&self, seconds: f64) -> Self {
+        // 2^64 units to the second
+        from_units(to_units(self).saturating_add((seconds * 18446744073709551616.0) as i128))
+    }
+
+    /// `duration_since` returns the time elapsed from `earlier` to this timestamp, or `None` if
+    /// `earlier` is later
+    pub fn duration_since(&self, earlier: TdmsTimestamp) -> Option<Duration> {
+        units_duration(to_units(self).checked_sub(to_units(&earlier))?)
+    }
+
+    /// `seconds_since` returns the signed number of seconds from `other` to this timestamp
+    pub fn seconds_since(&self, other: TdmsTimestamp) -> f64 {
+        (to_units(self) - to_units(&other)) as f64 / 18446744073709551616.0
+    }
+}
+
+/// to_units returns the timestamp as a single fixed point number of seconds
+fn to_units(timestamp: &TdmsTimestamp) -> i128 {
+    ((timestamp.0 as i128) << 64) | timestamp.1 as i128
+}
+
+fn from_units(units: i128) -> TdmsTimestamp {
+    TdmsTimestamp((units >> 64) as i64, units as u64)
+}
+
+fn duration_units(duration: Duration) -> Option<i128> {
+    let seconds = i64::try_from(duration.as_secs()).ok()? as i128;
+
+    Some(seconds << 64 | nanos_to_fraction(duration.subsec_nanos()) as i128)
+}
+
+fn units_duration(units: i128) -> Option<Duration> {
+    if units < 0 {
+        return None;
+    }
+
+    // rounding the fraction can make up a whole second, Duration::new carries it over
+    Some(Duration::new(
+        (units >> 64) as u64,
+        fraction_to_nanos(units as u64) as u32,
+    ))
+}
+
+fn nanos_to_fraction(nanos: u32) -> u64 {
+    ((((nanos as u128) << 64) + 500_000_000) / 1_000_000_000) as u64
+}
+
+/// fraction_to_nanos rounds to the nearest nanosecond, which can be a whole second
+fn fraction_to_nanos(fraction: u64) -> u64 {
+    ((fraction as u128 * 1_000_000_000 + (1 << 63)) >> 64) as u64
+}
+
+impl Add<Duration> for TdmsTimestamp {
+    type Output = TdmsTimestamp;
+
+    /// panics if the result overflows, use `checked_add` to avoid that
+    fn add(self, duration: Duration) -> Self::Output {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to timestamp")
+    }
+}
+
+impl AddAssign<Duration> for TdmsTimestamp {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl Sub<Duration> for TdmsTimestamp {
+    type Output = TdmsTimestamp;
+
+    /// panics if the result overflows, use `checked_sub` to avoid that
+    fn sub(self, duration: Duration) -> Self::Output {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from timestamp")
+    }
+}
+
+impl SubAssign<Duration> for TdmsTimestamp {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+impl TryFrom<Duration> for TdmsTimestamp {
+    type Error = TdmsError;
+
+    /// converts a duration since the TDMS epoch
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        TdmsTimestamp::EPOCH
+            .checked_add(duration)
+            .ok_or_else(|| General(String::from("duration is out of timestamp range")))
+    }
+}
+
+impl TryFrom<TdmsTimestamp> for Duration {
+    type Error = TdmsError;
+
+    /// converts to the duration since the TDMS epoch, which fails for earlier timestamps
+    fn try_from(timestamp: TdmsTimestamp) -> Result<Self, Self::Error> {
+        timestamp
+            .duration_since(TdmsTimestamp::EPOCH)
+            .ok_or_else(|| General(String::from("timestamp is before the TDMS epoch")))
+    }
+}
+
+impl TryFrom<SystemTime> for TdmsTimestamp {
+    type Error = TdmsError;
+
+    fn try_from(time: SystemTime) -> Result<Self, Self::Error> {
+        let timestamp = match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => TdmsTimestamp::UNIX_EPOCH.checked_add(after),
+            Err(before) => TdmsTimestamp::UNIX_EPOCH.checked_sub(before.duration()),
+        };
+
+        timestamp.ok_or_else(|| General(String::from("system time is out of timestamp range")))
+    }
+}
+
+impl TryFrom<TdmsTimestamp> for SystemTime {
+    type Error = TdmsError;
+
+    fn try_from(timestamp: TdmsTimestamp) -> Result<Self, Self::Error> {
+        let time = match timestamp.duration_since(TdmsTimestamp::UNIX_EPOCH) {
+            Some(after) => UNIX_EPOCH.checked_add(after),
+            None => TdmsTimestamp::UNIX_EPOCH
+                .duration_since(timestamp)
+                .and_then(|before| UNIX_EPOCH.checked_sub(before)),
+        };
+
+        time.ok_or_else(|| General(String::from("timestamp is out of system time range")))
+    }
+}
+
+impl fmt::Display for TdmsTimestamp {
+    /// formats the timestamp as an ISO-8601 date and time in UTC with nanosecond precision, e.g.
+    /// `2022-06-01T12:30:00.250000000Z`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self.to_unix_nanos();
+        let seconds = nanos.div_euclid(NANOS_PER_SECOND);
+        let days = seconds.div_euclid(86400);
+        let second_of_day = seconds.rem_euclid(86400);
+
+        // converts days since the Unix epoch to a civil date in the proleptic Gregorian calendar
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let day_of_era = z.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:09}Z",
+            year,
+            month,
+            day,
+            second_of_day / 3600,
+            second_of_day / 60 % 60,
+            second_of_day % 60,
+            nanos.rem_euclid(NANOS_PER_SECOND)
+        )
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::DateTime<chrono::Utc>> for TdmsTimestamp {
+    fn from(time: chrono::DateTime<chrono::Utc>) -> Self {
+        TdmsTimestamp(
+            time.timestamp() + UNIX_EPOCH_SECONDS,
+            nanos_to_fraction(time.timestamp_subsec_nanos()),
+        )
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<TdmsTimestamp> for chrono::DateTime<chrono::Utc> {
+    type Error = TdmsError;
+
+    fn try_from(timestamp: TdmsTimestamp) -> Result<Self, Self::Error> {
+        let nanos = timestamp.to_unix_nanos();
+
+        i64::try_from(nanos.div_euclid(NANOS_PER_SECOND))
+            .ok()
+            .and_then(|seconds| {
+                chrono::DateTime::from_timestamp(seconds, nanos.rem_euclid(NANOS_PER_SECOND) as u32)
+            })
+            .ok_or_else(|| General(String::from("timestamp is out of chrono range")))
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for TdmsTimestamp {
+    fn from(time: time::OffsetDateTime) -> Self {
+        TdmsTimestamp(
+            time.unix_timestamp() + UNIX_EPOCH_SECONDS,
+            nanos_to_fraction(time.nanosecond()),
+        )
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<TdmsTimestamp> for time::OffsetDateTime {
+    type Error = TdmsError;
+
+    fn try_from(timestamp: TdmsTimestamp) -> Result<Self, Self::Error> {
+        time::OffsetDateTime::from_unix_timestamp_nanos(timestamp.to_unix_nanos())
+            .map_err(|e| General(format!("timestamp is out of time range: {}", e)))
+    }
+}
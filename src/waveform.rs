@@ -0,0 +1,146 @@
+use crate::data_type::{TDMSValue, TdmsTimestamp};
+use crate::segment::MetadataProperty;
+use crate::{General, TdmsError};
+use std::ops::Range;
+
+/// name of the property holding the time of a waveform's first sample, before its start offset
+pub const START_TIME_PROPERTY: &str = "wf_start_time";
+/// name of the property holding the time in seconds between a waveform's samples
+pub const INCREMENT_PROPERTY: &str = "wf_increment";
+/// name of the property holding the offset in seconds of a waveform's first sample from its start
+/// time
+pub const START_OFFSET_PROPERTY: &str = "wf_start_offset";
+/// name of the property holding the number of samples in a waveform as it was written
+pub const SAMPLES_PROPERTY: &str = "wf_samples";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// `Waveform` describes the time axis of a channel written from a LabVIEW waveform - sample `n` was
+/// taken at `start_time` + `start_offset` + `n` * `increment`.
+pub struct Waveform {
+    pub start_time: TdmsTimestamp,
+    /// seconds between samples
+    pub increment: f64,
+    /// seconds from `start_time` to the first sample
+    pub start_offset: f64,
+    pub samples: Option<i32>,
+}
+
+impl Waveform {
+    /// `from_properties` reads the waveform from a channel's properties, properties later in the
+    /// list replacing earlier ones with the same name. Only `wf_increment` is required, waveforms
+    /// without a start time are relative to the TDMS epoch
+    pub fn from_properties<'b, I: IntoIterator<Item = &'b MetadataProperty>>(
+        properties: I,
+    ) -> Result<Self, TdmsError> {
+        let mut start_time: Option<&TDMSValue> = None;
+        let mut increment: Option<&TDMSValue> = None;
+        let mut start_offset: Option<&TDMSValue> = None;
+        let mut samples: Option<&TDMSValue> = None;
+
+        for property in properties {
+            match property.name.as_str() {
+                START_TIME_PROPERTY => start_time = Some(&property.value),
+                INCREMENT_PROPERTY => increment = Some(&property.value),
+                START_OFFSET_PROPERTY => start_offset = Some(&property.value),
+                SAMPLES_PROPERTY => samples = Some(&property.value),
+                _ => (),
+            }
+        }
+
+        let increment = match increment {
+            None => {
+                return Err(General(String::from(
+                    "channel is missing its waveform properties",
+                )))
+            }
+            Some(value) => value.as_f64()?,
+        };
+
+        Ok(Waveform {
+            start_time: match start_time {
+                None => TdmsTimestamp(0, 0),
+                Some(value) => value.as_timestamp()?,
+            },
+            increment,
+            start_offset: match start_offset {
+                None => 0.0,
+                Some(value) => value.as_f64()?,
+            },
+            samples: match samples {
+                None => None,
+                Some(value) => Some(i32::try_from(value.as_i64()?)?),
+            },
+        })
+    }
+
+    /// `time_of` returns the time sample `n` was taken at
+    pub fn time_of(&self, n: u64) -> TdmsTimestamp {
+        self.start_time
+            .add_seconds(self.start_offset + n as f64 * self.increment)
+    }
+
+    /// `samples_between` returns the indices of the samples taken from `start` up to but not
+    /// including `end`, out of a waveform of `len` samples
+    pub fn samples_between(
+        &self,
+        start: TdmsTimestamp,
+        end: TdmsTimestamp,
+        len: u64,
+    ) -> Range<u64> {
+        let first = self.first_sample_from(start, len);
+        let last = self.first_sample_from(end, len);
+
+        first..last.max(first)
+    }
+
+    /// first_sample_from returns the index of the first sample taken at or after `time`, estimating
+    /// it from the increment and then stepping past any rounding error
+    fn first_sample_from(&self, time: TdmsTimestamp, len: u64) -> u64 {
+        let estimate = time.seconds_since(self.time_of(0)) / self.increment;
+        let mut n = estimate.ceil().clamp(0.0, len as f64) as u64;
+
+        while n > 0 && self.time_of(n - 1) >= time {
+            n -= 1;
+        }
+        while n < len && self.time_of(n) < time {
+            n += 1;
+        }
+
+        n
+    }
+
+    /// `with_values` pairs each of the channel's values with the time it was taken at, the first
+    /// value being sample zero
+    pub fn with_values<I: IntoIterator>(&self, values: I) -> WaveformIter<I::IntoIter> {
+        WaveformIter {
+            waveform: *self,
+            values: values.into_iter(),
+            n: 0,
+        }
+    }
+}
+
+/// `WaveformIter` iterates through a channel's values along with the time each was taken at.
+pub struct WaveformIter<I: Iterator> {
+    waveform: Waveform,
+    values: I,
+    n: u64,
+}
+
+impl<I: Iterator> WaveformIter<I> {
+    pub fn waveform(&self) -> &Waveform {
+        &self.waveform
+    }
+}
+
+impl<I: Iterator> Iterator for WaveformIter<I> {
+    type Item = (TdmsTimestamp, I::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.values.next()?;
+        let time = self.waveform.time_of(self.n);
+        self.n += 1;
+
+        Some((time, value))
+    }
+}
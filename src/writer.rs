@@ -0,0 +1,528 @@
+use crate::data_type::{TDMSValue, TdmsDataType, TdmsNative};
+use crate::object_path::ObjectPath;
+use crate::segment::{
+    Endianness, RawDataIndex, Segment, K_TOC_BIG_ENDIAN, K_TOC_META_DATA, K_TOC_NEW_OBJ_LIST,
+    K_TOC_RAW_DATA,
+};
+use crate::{General, TDMSFile, TdmsError};
+use indexmap::{IndexMap, IndexSet};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// the version number LabVIEW writes into the lead in of TDMS 2.0 files
+const TDMS_VERSION: u32 = 4713;
+/// object has no raw data in this segment
+const NO_RAW_DATA: u32 = 0xFFFFFFFF;
+/// object's raw data index is identical to the one it used in the previous segment
+const RAW_DATA_INDEX_MATCHES_PREVIOUS: u32 = 0x00000000;
+
+#[derive(Debug)]
+/// `ChannelBuffer` holds a channel's encoded raw data until the next segment is written
+struct ChannelBuffer {
+    data_type: TdmsDataType,
+    number_of_values: u64,
+    data: Vec<u8>,
+    // string channels store the end offset of each value, these are written before the data itself
+    string_offsets: Vec<u32>,
+}
+
+impl ChannelBuffer {
+    fn raw_data_index(&self) -> RawDataIndex {
+        let number_of_bytes = match self.data_type {
+            TdmsDataType::String => {
+                Some(self.string_offsets.len() as u64 * 4 + self.data.len() as u64)
+            }
+            _ => None,
+        };
+
+        RawDataIndex {
+            data_type: self.data_type,
+            // TDMS only supports one dimensional arrays
+            array_dimension: 1,
+            number_of_values: self.number_of_values,
+            number_of_bytes,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// `TdmsWriter` creates TDMS files. Properties and raw data are buffered in memory and written as a
+/// single segment each time `write_segment` is called, so callers choose how large each segment is.
+pub struct TdmsWriter<W: Write> {
+    writer: W,
+    endianness: Endianness,
+    // every object path that has been written to the file at least once
+    written_objects: IndexSet<String>,
+    // objects which must be included in the next segment's metadata, in the order they were added
+    pending_objects: IndexSet<String>,
+    pending_properties: IndexMap<String, IndexMap<String, TDMSValue>>,
+    pending_data: IndexMap<String, ChannelBuffer>,
+    // paths of every channel object, as opposed to the root or group objects
+    channel_paths: HashSet<String>,
+    // the object list of the last segment written, mapping each channel to its raw data index
+    active_channels: IndexMap<String, Option<RawDataIndex>>,
+}
+
+impl TdmsWriter<BufWriter<File>> {
+    /// `create` creates a new TDMS file at the provided path, truncating it if it already exists.
+    pub fn create(path: &Path) -> Result<Self, TdmsError> {
+        let file = File::create(path)?;
+
+        Ok(TdmsWriter::new(BufWriter::new(file), Endianness::Little))
+    }
+
+    /// `append` opens an existing TDMS file and positions the writer after its last segment. The
+    /// last segment's object list and raw data indexes are loaded so that new segments with the same
+    /// channel layout can reuse them instead of repeating the full metadata. A last segment that was
+    /// never finished is closed first, dropping any incomplete chunk at its end. Empty files are
+    /// treated as new files.
+    pub fn append(path: &Path) -> Result<Self, TdmsError> {
+        let mut file = OpenOptions::new().write(true).open(path)?;
+
+        if file.metadata()?.len() == 0 {
+            return Ok(TdmsWriter::new(BufWriter::new(file), Endianness::Little));
+        }
+
+        // an index file can be stale, the file itself is the only reliable source of its segments
+        let mut tdms_file = TDMSFile::from_path_without_index(path)?;
+        let last_segment = match tdms_file.segments.last_mut() {
+            None => return Err(General(String::from("no segments found in file"))),
+            Some(s) => s,
+        };
+
+        if !last_segment.is_complete() {
+            finish_segment(&mut file, last_segment)?;
+        }
+        let last_segment = &tdms_file.segments[tdms_file.segments.len() - 1];
+
+        file.seek(SeekFrom::Start(last_segment.end_pos))?;
+
+        let mut writer = TdmsWriter::new(BufWriter::new(file), last_segment.endianess());
+
+        for segment in &tdms_file.segments {
+            match &segment.metadata {
+                None => continue,
+                Some(metadata) => {
+                    for obj in &metadata.objects {
+                        writer.written_objects.insert(obj.object_path.clone());
+                    }
+                }
+            }
+        }
+
+        // the object list's order is the order raw data has to be written in
+        let channels = last_segment
+            .channel_order
+            .iter()
+            .filter_map(|(group, path)| last_segment.get_channel(group, path));
+
+        for channel in channels {
+            writer.channel_paths.insert(channel.full_path.clone());
+            writer
+                .active_channels
+                .insert(channel.full_path.clone(), channel.raw_data_index.clone());
+        }
+
+        Ok(writer)
+    }
+}
+
+/// finish_segment closes a segment whose writer stopped before finishing it, so that segments can
+/// be appended after it. Any incomplete chunk at the end of its raw data is dropped, and its lead in
+/// is given the segment's real length in place of the offset marking it as still being written
+fn finish_segment(file: &mut File, segment: &mut Segment) -> Result<(), TdmsError> {
+    let end_pos = segment.truncate_to_whole_chunks();
+    file.set_len(end_pos)?;
+
+    let mut toc = segment.lead_in.table_of_contents;
+    if end_pos <= segment.start_pos + segment.lead_in.raw_data_offset {
+        toc &= !K_TOC_RAW_DATA;
+    }
+
+    let next_segment_offset = end_pos - segment.start_pos - 28;
+    let endianness = segment.endianess();
+    let mut lead_in: Vec<u8> = vec![];
+    toc.write_to(&mut lead_in, Endianness::Little)?;
+    segment
+        .lead_in
+        .version_number
+        .write_to(&mut lead_in, endianness)?;
+    next_segment_offset.write_to(&mut lead_in, endianness)?;
+
+    // the tag is left as it is, everything after the offset already holds the right values
+    file.seek(SeekFrom::Start(segment.start_pos + 4))?;
+    file.write_all(&lead_in)?;
+
+    segment.lead_in.table_of_contents = toc;
+    segment.lead_in.next_segment_offset = next_segment_offset;
+
+    Ok(())
+}
+
+impl<W: Write> TdmsWriter<W> {
+    /// `new` wraps any writer positioned at the point where the first segment should begin,
+    /// typically the start of a new file. All segments will be written using the endianness passed in.
+    pub fn new(writer: W, endianness: Endianness) -> Self {
+        TdmsWriter {
+            writer,
+            endianness,
+            written_objects: IndexSet::new(),
+            pending_objects: IndexSet::new(),
+            pending_properties: IndexMap::new(),
+            pending_data: IndexMap::new(),
+            channel_paths: HashSet::new(),
+            active_channels: IndexMap::new(),
+        }
+    }
+
+    /// sets a property on the root object of the file
+    pub fn set_file_property<V: Into<TDMSValue>>(&mut self, name: &str, value: V) {
+        let path = ObjectPath::Root.to_string();
+        self.add_object(path.clone());
+        self.set_property(path, name, value.into());
+    }
+
+    /// sets a property on a group, creating the group if it has not been written yet
+    pub fn set_group_property<V: Into<TDMSValue>>(&mut self, group: &str, name: &str, value: V) {
+        self.add_group(group);
+        self.set_property(ObjectPath::group(group).to_string(), name, value.into());
+    }
+
+    /// sets a property on a channel, creating the channel and its group if needed
+    pub fn set_channel_property<V: Into<TDMSValue>>(
+        &mut self,
+        group: &str,
+        channel: &str,
+        name: &str,
+        value: V,
+    ) {
+        self.add_channel(group, channel);
+        self.set_property(
+            ObjectPath::channel(group, channel).to_string(),
+            name,
+            value.into(),
+        );
+    }
+
+    /// add_group makes sure the group object is written in the next segment if it's new to the file
+    pub fn add_group(&mut self, group: &str) {
+        self.add_object(ObjectPath::Root.to_string());
+        self.add_object(ObjectPath::group(group).to_string());
+    }
+
+    /// add_channel makes sure the channel object is written in the next segment if it's new to the
+    /// file, a channel does not need raw data to exist
+    pub fn add_channel(&mut self, group: &str, channel: &str) {
+        self.add_group(group);
+
+        let path = ObjectPath::channel(group, channel).to_string();
+        self.channel_paths.insert(path.clone());
+        self.add_object(path);
+    }
+
+    /// add_channel_data buffers values for a channel, they will be written as part of the next
+    /// segment. All values buffered for a channel in a single segment must share the same type.
+    pub fn add_channel_data<T: TdmsNative>(
+        &mut self,
+        group: &str,
+        channel: &str,
+        values: &[T],
+    ) -> Result<(), TdmsError> {
+        self.add_channel(group, channel);
+        let path = ObjectPath::channel(group, channel).to_string();
+
+        let buffer = self
+            .pending_data
+            .entry(path.clone())
+            .or_insert(ChannelBuffer {
+                data_type: T::DATA_TYPE,
+                number_of_values: 0,
+                data: vec![],
+                string_offsets: vec![],
+            });
+
+        if buffer.data_type != T::DATA_TYPE {
+            return Err(General(format!(
+                "channel {} already has {:?} data buffered, unable to add {:?} values",
+                path,
+                buffer.data_type,
+                T::DATA_TYPE
+            )));
+        }
+
+        for value in values {
+            value.write_to(&mut buffer.data, self.endianness)?;
+
+            if T::DATA_TYPE == TdmsDataType::String {
+                buffer
+                    .string_offsets
+                    .push(u32::try_from(buffer.data.len())?);
+            }
+        }
+
+        buffer.number_of_values += values.len() as u64;
+
+        Ok(())
+    }
+
+    /// write_segment writes everything buffered since the last segment to the underlying writer.
+    /// Nothing is written if there are no pending properties, objects or data. When exactly the
+    /// channels that had data in the previous segment have data again, the previous object list is
+    /// reused and only new or changed objects are written to the segment's metadata.
+    pub fn write_segment(&mut self) -> Result<(), TdmsError> {
+        if self.pending_objects.is_empty() && self.pending_data.is_empty() {
+            return Ok(());
+        }
+
+        let active_with_data: Vec<&String> = self
+            .active_channels
+            .iter()
+            .filter(|(_, index)| index.is_some())
+            .map(|(path, _)| path)
+            .collect();
+
+        let layout_unchanged = !self.written_objects.is_empty()
+            && active_with_data.len() == self.pending_data.len()
+            && active_with_data
+                .iter()
+                .all(|path| self.pending_data.contains_key(*path));
+
+        // objects listed in the metadata, and the order raw data is written in
+        let mut objects: IndexSet<String> = self.pending_objects.clone();
+        let data_order: Vec<String>;
+
+        if layout_unchanged {
+            data_order = active_with_data.into_iter().cloned().collect();
+
+            // channels whose index changed, e.g. a different number of values, must be listed
+            for (path, buffer) in &self.pending_data {
+                if self.active_channels.get(path) != Some(&Some(buffer.raw_data_index())) {
+                    objects.insert(path.clone());
+                }
+            }
+        } else {
+            for path in self.pending_data.keys() {
+                objects.insert(path.clone());
+            }
+
+            data_order = objects
+                .iter()
+                .filter(|path| self.pending_data.contains_key(*path))
+                .cloned()
+                .collect();
+        }
+
+        let mut toc = 0;
+        if !objects.is_empty() {
+            toc |= K_TOC_META_DATA;
+        }
+        if !layout_unchanged {
+            toc |= K_TOC_NEW_OBJ_LIST;
+        }
+        if self.endianness == Endianness::Big {
+            toc |= K_TOC_BIG_ENDIAN;
+        }
+
+        let mut metadata: Vec<u8> = vec![];
+
+        if !objects.is_empty() {
+            (objects.len() as u32).write_to(&mut metadata, self.endianness)?;
+        }
+
+        for path in &objects {
+            write_string(&mut metadata, path, self.endianness)?;
+
+            match self.pending_data.get(path) {
+                None => NO_RAW_DATA.write_to(&mut metadata, self.endianness)?,
+                Some(buffer) => {
+                    let index = buffer.raw_data_index();
+
+                    if self.active_channels.get(path) == Some(&Some(index.clone())) {
+                        RAW_DATA_INDEX_MATCHES_PREVIOUS.write_to(&mut metadata, self.endianness)?
+                    } else {
+                        write_raw_data_index(&mut metadata, &index, self.endianness)?
+                    }
+                }
+            }
+
+            match self.pending_properties.get(path) {
+                None => 0u32.write_to(&mut metadata, self.endianness)?,
+                Some(properties) => {
+                    (properties.len() as u32).write_to(&mut metadata, self.endianness)?;
+
+                    for (name, value) in properties {
+                        write_string(&mut metadata, name, self.endianness)?;
+                        write_value(&mut metadata, value, self.endianness)?;
+                    }
+                }
+            }
+        }
+
+        let mut raw_data: Vec<u8> = vec![];
+
+        for path in &data_order {
+            match self.pending_data.get(path) {
+                None => continue,
+                Some(buffer) => {
+                    for offset in &buffer.string_offsets {
+                        offset.write_to(&mut raw_data, self.endianness)?;
+                    }
+
+                    raw_data.extend_from_slice(&buffer.data);
+                }
+            }
+        }
+
+        if !raw_data.is_empty() {
+            toc |= K_TOC_RAW_DATA;
+        }
+
+        self.write_lead_in(toc, metadata.len() as u64, raw_data.len() as u64)?;
+        self.writer.write_all(&metadata)?;
+        self.writer.write_all(&raw_data)?;
+
+        // keep track of the object list exactly as a reader will see it after this segment
+        if !layout_unchanged {
+            self.active_channels.clear();
+        }
+
+        for path in &objects {
+            if !self.channel_paths.contains(path) {
+                continue;
+            }
+
+            let index = self.pending_data.get(path).map(|b| b.raw_data_index());
+            self.active_channels.insert(path.clone(), index);
+        }
+
+        for path in objects {
+            self.written_objects.insert(path);
+        }
+        self.pending_objects.clear();
+        self.pending_properties.clear();
+        self.pending_data.clear();
+
+        Ok(())
+    }
+
+    /// flush writes any pending segment and flushes the underlying writer
+    pub fn flush(&mut self) -> Result<(), TdmsError> {
+        self.write_segment()?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+
+    /// into_inner flushes the writer and returns the underlying writer
+    pub fn into_inner(mut self) -> Result<W, TdmsError> {
+        self.flush()?;
+
+        Ok(self.writer)
+    }
+
+    fn add_object(&mut self, path: String) {
+        if !self.written_objects.contains(&path) {
+            self.pending_objects.insert(path);
+        }
+    }
+
+    fn set_property(&mut self, path: String, name: &str, value: TDMSValue) {
+        self.pending_objects.insert(path.clone());
+        self.pending_properties
+            .entry(path)
+            .or_default()
+            .insert(String::from(name), value);
+    }
+
+    fn write_lead_in(
+        &mut self,
+        table_of_contents: u32,
+        metadata_len: u64,
+        raw_data_len: u64,
+    ) -> Result<(), TdmsError> {
+        let mut lead_in: Vec<u8> = Vec::with_capacity(28);
+        lead_in.extend_from_slice(b"TDSm");
+        // the table of contents is always little endian, regardless of the rest of the segment
+        table_of_contents.write_to(&mut lead_in, Endianness::Little)?;
+        TDMS_VERSION.write_to(&mut lead_in, self.endianness)?;
+        (metadata_len + raw_data_len).write_to(&mut lead_in, self.endianness)?;
+        metadata_len.write_to(&mut lead_in, self.endianness)?;
+
+        self.writer.write_all(&lead_in)?;
+
+        Ok(())
+    }
+}
+
+fn write_raw_data_index(
+    buf: &mut Vec<u8>,
+    index: &RawDataIndex,
+    endianness: Endianness,
+) -> Result<(), TdmsError> {
+    // length of the index itself, strings carry an extra u64 for their total size in bytes
+    let index_len: u32 = match index.number_of_bytes {
+        None => 20,
+        Some(_) => 28,
+    };
+
+    index_len.write_to(buf, endianness)?;
+    i32::from(index.data_type).write_to(buf, endianness)?;
+    index.array_dimension.write_to(buf, endianness)?;
+    index.number_of_values.write_to(buf, endianness)?;
+
+    match index.number_of_bytes {
+        None => (),
+        Some(number_of_bytes) => number_of_bytes.write_to(buf, endianness)?,
+    }
+
+    Ok(())
+}
+
+fn write_string(buf: &mut Vec<u8>, value: &str, endianness: Endianness) -> Result<(), TdmsError> {
+    u32::try_from(value.len())?.write_to(buf, endianness)?;
+    buf.extend_from_slice(value.as_bytes());
+
+    Ok(())
+}
+
+/// write_value writes a property's data type and value, swapping the value's bytes if it was
+/// encoded with a different endianness than the segment's
+fn write_value(
+    buf: &mut Vec<u8>,
+    value: &TDMSValue,
+    endianness: Endianness,
+) -> Result<(), TdmsError> {
+    i32::from(value.data_type).write_to(buf, endianness)?;
+
+    let bytes = match &value.value {
+        None => return Ok(()),
+        Some(bytes) => bytes,
+    };
+
+    match value.data_type {
+        TdmsDataType::String => write_string(
+            buf,
+            match std::str::from_utf8(bytes) {
+                Ok(s) => s,
+                Err(_) => {
+                    return Err(General(String::from(
+                        "string property value is not valid UTF-8",
+                    )))
+                }
+            },
+            endianness,
+        )?,
+        _ if value.endianness == endianness => buf.extend_from_slice(bytes),
+        // complex numbers are a pair of floats, each must be swapped on its own
+        TdmsDataType::ComplexSingleFloat(_) | TdmsDataType::ComplexDoubleFloat(_) => {
+            let (re, im) = bytes.split_at(bytes.len() / 2);
+            buf.extend(re.iter().rev());
+            buf.extend(im.iter().rev());
+        }
+        _ => buf.extend(bytes.iter().rev()),
+    }
+
+    Ok(())
+}